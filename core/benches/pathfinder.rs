@@ -0,0 +1,108 @@
+// See LICENSE file for copyright and license details.
+
+use cgmath::Vector2;
+use criterion::{criterion_group, criterion_main, Criterion};
+use marauder_core::core::{ObjectTypes, Unit};
+use marauder_core::dir::Dir;
+use marauder_core::fs::FileSystem;
+use marauder_core::game_state::GameState;
+use marauder_core::map::TerrainMap;
+use marauder_core::pathfinder::{get_move_cost, Pathfinder};
+use marauder_core::types::{MInt, MapPos, PlayerId, Size2, UnitId};
+use std::collections::VecDeque;
+use std::path::Path;
+
+const MAP_SIZE: Size2<MInt> = Size2 { w: 200, h: 200 };
+
+fn get_object_types() -> ObjectTypes {
+    let fs = FileSystem::with_root(&Path::new(env!("CARGO_MANIFEST_DIR")).join(".."));
+    ObjectTypes::new(&fs.get(Path::new("data/object_types.json")))
+}
+
+// Terrain symbols are repeated to get a map with different move costs
+fn get_state(object_types: &ObjectTypes) -> GameState {
+    let pattern: Vec<char> = "..=.f..#..~.=..f.".chars().collect();
+    let mut rows = Vec::new();
+    for y in 0..MAP_SIZE.h {
+        let row = (0..MAP_SIZE.w)
+            .map(|x| pattern[((x * 7 + y * 3) as usize) % pattern.len()])
+            .collect();
+        rows.push(row);
+    }
+    let mut state = GameState::new(TerrainMap::from_rows(object_types, MAP_SIZE, &rows));
+    let id = UnitId { id: 0 };
+    let _ = state.units.insert(
+        id,
+        Unit {
+            id,
+            pos: MapPos {
+                v: Vector2 { x: 0, y: 0 },
+            },
+            player_id: PlayerId { id: 0 },
+            type_id: object_types.get_unit_type_id("soldier"),
+            move_points: 100000,
+            attacked: false,
+            overwatch: false,
+            count: 1,
+        },
+    );
+    state
+}
+
+// Baseline: the old FIFO breadth-first search. With weighted move costs
+// it has to queue a tile again every time a cheaper way to it is found.
+fn bfs_fill_map(object_types: &ObjectTypes, state: &GameState, unit: &Unit) -> Vec<MInt> {
+    let index = |pos: MapPos| (pos.v.x + pos.v.y * MAP_SIZE.w) as usize;
+    let mut costs = vec![MInt::MAX; (MAP_SIZE.w * MAP_SIZE.h) as usize];
+    let mut queue = VecDeque::new();
+    costs[index(unit.pos)] = 0;
+    queue.push_back(unit.pos);
+    while let Some(pos) = queue.pop_front() {
+        for i in 0..6 {
+            let neighbour_pos = Dir::get_neighbour_pos(pos, Dir::from_int(i));
+            if !state.map.is_inboard(neighbour_pos) {
+                continue;
+            }
+            let move_cost = match get_move_cost(object_types, state, unit, neighbour_pos) {
+                Some(move_cost) => move_cost,
+                None => continue,
+            };
+            let new_cost = costs[index(pos)] + move_cost;
+            if new_cost < costs[index(neighbour_pos)]
+                && new_cost <= unit.move_points
+                && state.units_at(neighbour_pos).is_empty()
+            {
+                costs[index(neighbour_pos)] = new_cost;
+                queue.push_back(neighbour_pos);
+            }
+        }
+    }
+    costs
+}
+
+fn pathfinder_benchmark(c: &mut Criterion) {
+    let object_types = get_object_types();
+    let state = get_state(&object_types);
+    let unit = &state.units[&UnitId { id: 0 }];
+    let destination = MapPos {
+        v: Vector2 {
+            x: MAP_SIZE.w - 1,
+            y: MAP_SIZE.h - 1,
+        },
+    };
+    let mut pathfinder = Pathfinder::new(MAP_SIZE);
+    c.bench_function("bfs_fill_map (baseline)", |b| {
+        b.iter(|| bfs_fill_map(&object_types, &state, unit))
+    });
+    c.bench_function("fill_map", |b| {
+        b.iter(|| pathfinder.fill_map(&object_types, &state, unit))
+    });
+    c.bench_function("find_path", |b| {
+        b.iter(|| pathfinder.find_path(&object_types, &state, unit, destination))
+    });
+}
+
+criterion_group!(benches, pathfinder_benchmark);
+criterion_main!(benches);
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,153 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::Command::{CommandAttackUnit, CommandEndTurn, CommandMove, CommandOverwatch};
+use crate::combat::{get_best_weapon_index, predict};
+use crate::core::{Command, ObjectTypes, Unit};
+use crate::game_state::GameState;
+use crate::map::{distance, MapPosIter};
+use crate::pathfinder::Pathfinder;
+use crate::types::{MInt, MapPos, PlayerId, Size2, UnitId};
+use cgmath::Vector2;
+
+// Computer player: looks only at its own view of the game
+// and gives one command at a time until it ends the turn.
+pub struct Ai {
+    player_id: PlayerId,
+    pathfinder: Pathfinder,
+}
+
+impl Ai {
+    pub fn new(player_id: PlayerId, map_size: Size2<MInt>) -> Ai {
+        Ai {
+            player_id,
+            pathfinder: Pathfinder::new(map_size),
+        }
+    }
+
+    fn get_own_units<'a>(&self, state: &'a GameState) -> Vec<&'a Unit> {
+        let mut units: Vec<&Unit> = state
+            .units
+            .values()
+            .filter(|unit| unit.player_id == self.player_id)
+            .collect();
+        units.sort_by_key(|unit| unit.id);
+        units
+    }
+
+    fn get_enemy_units<'a>(&self, state: &'a GameState) -> Vec<&'a Unit> {
+        let mut units: Vec<&Unit> = state
+            .units
+            .values()
+            .filter(|unit| unit.player_id != self.player_id)
+            .collect();
+        units.sort_by_key(|unit| unit.id);
+        units
+    }
+
+    // Enemy in line of fire with the best kill chance and the weapon to use
+    fn get_best_target(
+        &self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        unit: &Unit,
+    ) -> Option<(UnitId, MInt)> {
+        let mut best_target = None;
+        let mut best_chance = 0;
+        for enemy in self.get_enemy_units(state).into_iter() {
+            let weapon_index = match get_best_weapon_index(object_types, state, unit, enemy) {
+                Some(index) => index,
+                None => continue,
+            };
+            let chance = predict(object_types, unit, enemy, weapon_index).kill;
+            if chance > best_chance {
+                best_chance = chance;
+                best_target = Some((enemy.id, weapon_index));
+            }
+        }
+        best_target
+    }
+
+    // Where to go if no enemies are visible
+    fn get_default_destination(state: &GameState) -> MapPos {
+        let size = state.map.get_size();
+        MapPos {
+            v: Vector2 {
+                x: size.w / 2,
+                y: size.h / 2,
+            },
+        }
+    }
+
+    fn get_distance_to_goal(&self, state: &GameState, pos: MapPos) -> MInt {
+        let enemies = self.get_enemy_units(state);
+        if enemies.is_empty() {
+            distance(pos, Ai::get_default_destination(state))
+        } else {
+            enemies
+                .iter()
+                .map(|enemy| distance(pos, enemy.pos))
+                .min()
+                .unwrap()
+        }
+    }
+
+    // Reachable tile that is closest to the enemies,
+    // units with enemies in line of fire do not move.
+    fn get_move_path(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        unit: &Unit,
+    ) -> Option<Vec<MapPos>> {
+        if unit.move_points == 0 || unit.overwatch {
+            return None;
+        }
+        if self.get_best_target(object_types, state, unit).is_some() {
+            return None;
+        }
+        let current_distance = self.get_distance_to_goal(state, unit.pos);
+        self.pathfinder.fill_map(object_types, state, unit);
+        let mut best_pos = None;
+        let mut best_distance = current_distance;
+        let mut best_cost = 0;
+        for pos in MapPosIter::new(state.map.get_size()) {
+            if pos == unit.pos || self.pathfinder.get_path(pos).is_none() {
+                continue;
+            }
+            let distance = self.get_distance_to_goal(state, pos);
+            let cost = self.pathfinder.get_map().tile(pos).cost;
+            if distance < best_distance || (distance == best_distance && cost < best_cost) {
+                best_pos = Some(pos);
+                best_distance = distance;
+                best_cost = cost;
+            }
+        }
+        match best_pos {
+            Some(pos) => self.pathfinder.get_path(pos),
+            None => None,
+        }
+    }
+
+    // CommandEndTurn when all units are done,
+    // units that did not fire keep their attack on overwatch
+    pub fn get_command(&mut self, object_types: &ObjectTypes, state: &GameState) -> Command {
+        for unit in self.get_own_units(state).into_iter() {
+            if !unit.attacked && !unit.overwatch {
+                if let Some((target_id, weapon_index)) =
+                    self.get_best_target(object_types, state, unit)
+                {
+                    return CommandAttackUnit(unit.id, target_id, Some(weapon_index));
+                }
+            }
+            if let Some(path) = self.get_move_path(object_types, state, unit) {
+                return CommandMove(unit.id, path);
+            }
+            if !unit.attacked && !unit.overwatch {
+                return CommandOverwatch(unit.id);
+            }
+        }
+        CommandEndTurn
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,254 @@
+// See LICENSE file for copyright and license details.
+
+// Every alive model of the attacker fires one shot. Shot kills one model
+// of the defender if it passes three tests in a row: hit, pierce and wound.
+// Every test rolls a number in [ROLL_MIN, ROLL_MAX) that must be less
+// than the needed value. Predictions count the same rolls.
+
+use crate::core::{ObjectTypes, Unit};
+use crate::game_state::GameState;
+use crate::los::check_line_of_fire;
+use crate::types::MInt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const ROLL_MIN: MInt = -5;
+const ROLL_MAX: MInt = 5;
+
+// Needed values of hit, pierce and wound tests
+pub fn get_test_values(
+    object_types: &ObjectTypes,
+    attacker: &Unit,
+    defender: &Unit,
+    weapon_index: MInt,
+) -> (MInt, MInt, MInt) {
+    let attacker_type = object_types.get_unit_type(attacker.type_id);
+    let defender_type = object_types.get_unit_type(defender.type_id);
+    let weapon_type = object_types.get_unit_weapon_type(attacker.type_id, weapon_index);
+    let hit_test_v = -15 + defender_type.size + weapon_type.accuracy + attacker_type.weapon_skill;
+    let pierce_test_v = 5 + -defender_type.armor + weapon_type.ap;
+    let wound_test_v = -defender_type.toughness + weapon_type.damage;
+    (hit_test_v, pierce_test_v, wound_test_v)
+}
+
+pub fn roll<R: Rng>(rng: &mut R) -> MInt {
+    rng.gen_range(ROLL_MIN, ROLL_MAX)
+}
+
+pub fn is_passed(roll: MInt, needed: MInt) -> bool {
+    roll < needed
+}
+
+// Share of rolls that pass the test, in percents
+pub fn get_pass_chance(needed: MInt) -> MInt {
+    let rolls_count = ROLL_MAX - ROLL_MIN;
+    let passed_rolls_count = std::cmp::max(0, std::cmp::min(rolls_count, needed - ROLL_MIN));
+    passed_rolls_count * 100 / rolls_count
+}
+
+// Chances in percents. Test chances are of one shot: every test after
+// the hit one counts only shots that passed the previous tests.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AttackPrediction {
+    pub hit: MInt,
+    pub pierce: MInt,
+    pub wound: MInt,
+    // one shot kills a model
+    pub kill: MInt,
+    pub shots: MInt,
+    // all shots together kill at least one model
+    pub any_kill: MInt,
+    // all shots together kill every model of the defender
+    pub destroy: MInt,
+}
+
+fn get_binomial_coefficient(n: MInt, k: MInt) -> f64 {
+    let mut coefficient = 1.0;
+    for i in 0..k {
+        coefficient = coefficient * (n - i) as f64 / (i + 1) as f64;
+    }
+    coefficient
+}
+
+// Chance that at least min_kills of the shots kill
+fn get_kills_chance(shot_chance: f64, shots: MInt, min_kills: MInt) -> f64 {
+    let mut chance = 0.0;
+    for kills in min_kills..shots + 1 {
+        chance += get_binomial_coefficient(shots, kills)
+            * shot_chance.powi(kills)
+            * (1.0 - shot_chance).powi(shots - kills);
+    }
+    chance
+}
+
+// Percents are rounded down like the test chances
+fn to_percents(chance: f64) -> MInt {
+    (chance * 100.0 + 1e-9) as MInt
+}
+
+pub fn predict(
+    object_types: &ObjectTypes,
+    attacker: &Unit,
+    defender: &Unit,
+    weapon_index: MInt,
+) -> AttackPrediction {
+    let (hit_test_v, pierce_test_v, wound_test_v) =
+        get_test_values(object_types, attacker, defender, weapon_index);
+    let hit = get_pass_chance(hit_test_v);
+    let pierce = get_pass_chance(pierce_test_v);
+    let wound = get_pass_chance(wound_test_v);
+    let shot_chance = (hit * pierce * wound) as f64 / (100.0 * 100.0 * 100.0);
+    let shots = attacker.count;
+    AttackPrediction {
+        hit,
+        pierce,
+        wound,
+        kill: hit * pierce * wound / (100 * 100),
+        shots,
+        any_kill: to_percents(get_kills_chance(shot_chance, shots, 1)),
+        destroy: to_percents(get_kills_chance(shot_chance, shots, defender.count)),
+    }
+}
+
+// Named by the first failed test
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AttackOutcome {
+    Miss,
+    NoPierce,
+    NoWound,
+    Killed,
+}
+
+impl fmt::Display for AttackOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            AttackOutcome::Miss => "miss",
+            AttackOutcome::NoPierce => "armor deflected",
+            AttackOutcome::NoWound => "no wound",
+            AttackOutcome::Killed => "killed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TestRoll {
+    pub roll: MInt,
+    pub needed: MInt,
+}
+
+impl TestRoll {
+    pub fn is_passed(&self) -> bool {
+        is_passed(self.roll, self.needed)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ShotResult {
+    pub outcome: AttackOutcome,
+    // hit, pierce and wound tests till the first failed one
+    pub rolls: Vec<TestRoll>,
+}
+
+impl ShotResult {
+    pub fn is_killed(&self) -> bool {
+        self.outcome == AttackOutcome::Killed
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AttackResult {
+    // index in attacker's weapons
+    pub weapon_index: MInt,
+    pub shots: Vec<ShotResult>,
+}
+
+impl AttackResult {
+    pub fn killed_count(&self) -> MInt {
+        self.shots.iter().filter(|shot| shot.is_killed()).count() as MInt
+    }
+
+    // Defender must be taken from the state before the attack
+    pub fn is_destroyed(&self, defender: &Unit) -> bool {
+        self.killed_count() >= defender.count
+    }
+}
+
+fn shoot<R: Rng>(rng: &mut R, test_values: (MInt, MInt, MInt)) -> ShotResult {
+    let (hit_test_v, pierce_test_v, wound_test_v) = test_values;
+    let tests = [
+        (hit_test_v, AttackOutcome::Miss),
+        (pierce_test_v, AttackOutcome::NoPierce),
+        (wound_test_v, AttackOutcome::NoWound),
+    ];
+    let mut rolls = Vec::new();
+    for &(needed, failed_outcome) in tests.iter() {
+        let test_roll = TestRoll {
+            roll: roll(rng),
+            needed,
+        };
+        rolls.push(test_roll);
+        if !test_roll.is_passed() {
+            return ShotResult {
+                outcome: failed_outcome,
+                rolls,
+            };
+        }
+    }
+    ShotResult {
+        outcome: AttackOutcome::Killed,
+        rolls,
+    }
+}
+
+// No more shots when all models of the defender are killed
+pub fn attack<R: Rng>(
+    rng: &mut R,
+    object_types: &ObjectTypes,
+    attacker: &Unit,
+    defender: &Unit,
+    weapon_index: MInt,
+) -> AttackResult {
+    let test_values = get_test_values(object_types, attacker, defender, weapon_index);
+    let mut result = AttackResult {
+        weapon_index,
+        shots: Vec::new(),
+    };
+    for _ in 0..attacker.count {
+        if result.is_destroyed(defender) {
+            break;
+        }
+        result.shots.push(shoot(rng, test_values));
+    }
+    result
+}
+
+// Weapon in line of fire with the best kill chance,
+// the first one of equally good weapons
+pub fn get_best_weapon_index(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    attacker: &Unit,
+    defender: &Unit,
+) -> Option<MInt> {
+    let weapons_count = object_types
+        .get_unit_type(attacker.type_id)
+        .weapon_type_ids
+        .len() as MInt;
+    let mut best_index = None;
+    let mut best_chance = -1;
+    for index in 0..weapons_count {
+        if check_line_of_fire(object_types, state, attacker, index, defender.pos).is_err() {
+            continue;
+        }
+        let chance = predict(object_types, attacker, defender, index).kill;
+        if chance > best_chance {
+            best_index = Some(index);
+            best_chance = chance;
+        }
+    }
+    best_index
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -1,6 +1,6 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::misc::read_file;
+use crate::misc::read_file;
 use std::path::Path;
 
 pub struct Config {
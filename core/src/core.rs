@@ -0,0 +1,996 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::Event::{
+    EventAttackUnit, EventCreateUnit, EventEndTurn, EventGameOver, EventMove, EventOverwatch,
+    EventShowUnit,
+};
+use crate::combat::{self, get_best_weapon_index, AttackResult};
+use crate::fow::{is_visible, Fow};
+use crate::fs::FileSystem;
+use crate::game_state::GameState;
+use crate::los::check_line_of_fire;
+use crate::map::{distance, TerrainMap};
+use crate::misc::{read_file, write_file};
+use crate::pathfinder::get_move_cost;
+use crate::replay::{Replay, ReplayEntry};
+use crate::scenario::Scenario;
+use crate::types::{MInt, MapPos, PlayerId, Size2, UnitId};
+use crate::victory::{GameResult, Victory};
+use crate::zoc::is_move_stopped_at;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Command {
+    CommandMove(UnitId, Vec<MapPos>),
+    CommandEndTurn,
+    CommandCreateUnit(MapPos),
+    // attacker, defender and index in attacker's weapons,
+    // None picks the weapon with the best kill chance
+    CommandAttackUnit(UnitId, UnitId, Option<MInt>),
+    // unit keeps its attack for enemy's turn and fires at the first
+    // enemy that moves into its sight and weapon range
+    CommandOverwatch(UnitId),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CommandError {
+    NoSuchUnit,
+    NotOwnUnit,
+    CanNotAttackOwnUnit,
+    TargetIsNotVisible,
+    NoMovePoints,
+    NotEnoughMovePoints,
+    BadPath,
+    OutOfMap,
+    TileIsImpassable,
+    TileIsOccupied,
+    ZoneOfControl,
+    AlreadyAttacked,
+    OnOverwatch,
+    NoSuchWeapon,
+    OutOfRange,
+    NoLineOfSight,
+    GameIsOver,
+    // network game only
+    NotYourTurn,
+    Disconnected,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            CommandError::NoSuchUnit => "no such unit",
+            CommandError::NotOwnUnit => "unit belongs to another player",
+            CommandError::CanNotAttackOwnUnit => "can not attack own unit",
+            CommandError::TargetIsNotVisible => "target is not visible",
+            CommandError::NoMovePoints => "unit has no move points",
+            CommandError::NotEnoughMovePoints => "not enough move points",
+            CommandError::BadPath => "bad path",
+            CommandError::OutOfMap => "position is out of map",
+            CommandError::TileIsImpassable => "tile is impassable",
+            CommandError::TileIsOccupied => "tile is occupied",
+            CommandError::ZoneOfControl => "path goes on through enemy zone of control",
+            CommandError::AlreadyAttacked => "unit has already attacked",
+            CommandError::OnOverwatch => "unit is on overwatch",
+            CommandError::NoSuchWeapon => "unit has no such weapon",
+            CommandError::OutOfRange => "target is out of range",
+            CommandError::NoLineOfSight => "target is not in line of sight",
+            CommandError::GameIsOver => "game is over",
+            CommandError::NotYourTurn => "it is not your turn",
+            CommandError::Disconnected => "disconnected from server",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Event {
+    EventMove(UnitId, Vec<MapPos>),
+    EventEndTurn(PlayerId, PlayerId),
+    // old_id, new_id
+    EventCreateUnit(UnitId, MapPos, UnitTypeId, PlayerId),
+    EventAttackUnit(UnitId, UnitId, AttackResult),
+    EventOverwatch(UnitId),
+    // Enemy unit came into view of the player (player events only)
+    EventShowUnit(Unit),
+    // Enemy unit left the view of the player (player events only)
+    EventHideUnit(UnitId),
+    // Always the last event of a game
+    EventGameOver(GameResult),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub id: PlayerId,
+    pub name: String,
+    pub is_ai: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitClass {
+    Infantry,
+    Vehicle,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponType {
+    pub name: String,
+    pub damage: MInt,
+    pub ap: MInt,
+    pub accuracy: MInt,
+    pub max_distance: MInt,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerrainType {
+    pub name: String,
+    // used in scenario maps
+    pub symbol: char,
+    // unit classes that are missing here can not enter tiles of this type
+    pub move_cost: HashMap<UnitClass, MInt>,
+    // ground level: units on higher tiles can look over obstacles
+    #[serde(default)]
+    pub elevation: MInt,
+    // forests and buildings block sight and fire like one more level
+    #[serde(default)]
+    pub blocks_sight: bool,
+}
+
+impl TerrainType {
+    pub fn move_cost(&self, class: UnitClass) -> Option<MInt> {
+        self.move_cost.get(&class).cloned()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TerrainTypeId {
+    pub id: MInt,
+}
+
+#[derive(Copy, Clone)]
+pub struct WeaponTypeId {
+    pub id: MInt,
+}
+
+pub struct UnitType {
+    pub name: String,
+    pub class: UnitClass,
+    pub count: MInt,
+    pub size: MInt,
+    pub armor: MInt,
+    pub toughness: MInt,
+    pub weapon_skill: MInt,
+    // main weapon goes first
+    pub weapon_type_ids: Vec<WeaponTypeId>,
+    pub move_points: MInt,
+    pub sight_range: MInt,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UnitTypeId {
+    pub id: MInt,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Unit {
+    pub id: UnitId,
+    pub pos: MapPos,
+    pub player_id: PlayerId,
+    pub type_id: UnitTypeId,
+    pub move_points: MInt,
+    pub attacked: bool,
+    // attack is reserved for reaction fire till the next own turn
+    pub overwatch: bool,
+    // alive models, unit is destroyed when it is zero
+    pub count: MInt,
+}
+
+// Unit type as it is written in object types file:
+// weapons are referenced by names and resolved at load time.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnitTypeInfo {
+    name: String,
+    class: UnitClass,
+    count: MInt,
+    size: MInt,
+    armor: MInt,
+    toughness: MInt,
+    weapon_skill: MInt,
+    weapons: Vec<String>,
+    move_points: MInt,
+    sight_range: MInt,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectTypesInfo {
+    // unit classes that stop next to enemies
+    #[serde(default)]
+    zone_of_control: Vec<UnitClass>,
+    terrain_types: Vec<TerrainType>,
+    weapon_types: Vec<WeaponType>,
+    unit_types: Vec<UnitTypeInfo>,
+}
+
+pub struct ObjectTypes {
+    terrain_types: Vec<TerrainType>,
+    unit_types: Vec<UnitType>,
+    weapon_types: Vec<WeaponType>,
+    zone_of_control_classes: Vec<UnitClass>,
+}
+
+impl ObjectTypes {
+    pub fn new(path: &Path) -> ObjectTypes {
+        let info: ObjectTypesInfo = match serde_json::from_str(&read_file(path)) {
+            Ok(info) => info,
+            Err(msg) => panic!(
+                "Can not parse object types from {}: {}",
+                path.display(),
+                msg
+            ),
+        };
+        let mut object_types = ObjectTypes {
+            terrain_types: Vec::new(),
+            unit_types: Vec::new(),
+            weapon_types: Vec::new(),
+            zone_of_control_classes: info.zone_of_control,
+        };
+        object_types.add_terrain_types(info.terrain_types);
+        object_types.add_weapon_types(info.weapon_types);
+        object_types.add_unit_types(info.unit_types);
+        object_types
+    }
+
+    fn add_terrain_types(&mut self, terrain_types: Vec<TerrainType>) {
+        if terrain_types.is_empty() {
+            panic!("No terrain types");
+        }
+        for terrain_type in terrain_types.into_iter() {
+            for other in self.terrain_types.iter() {
+                if other.name == terrain_type.name {
+                    panic!("Duplicate terrain type name: \"{}\"", terrain_type.name);
+                }
+                if other.symbol == terrain_type.symbol {
+                    panic!(
+                        "Terrain types \"{}\" and \"{}\" have the same symbol: '{}'",
+                        other.name, terrain_type.name, terrain_type.symbol
+                    );
+                }
+            }
+            for (_, &cost) in terrain_type.move_cost.iter() {
+                if cost <= 0 {
+                    panic!(
+                        "Terrain type \"{}\": bad move cost: {}",
+                        terrain_type.name, cost
+                    );
+                }
+            }
+            self.terrain_types.push(terrain_type);
+        }
+    }
+
+    fn add_weapon_types(&mut self, weapon_types: Vec<WeaponType>) {
+        for weapon_type in weapon_types.into_iter() {
+            if self.get_weapon_type_id_opt(&weapon_type.name).is_some() {
+                panic!("Duplicate weapon type name: \"{}\"", weapon_type.name);
+            }
+            self.weapon_types.push(weapon_type);
+        }
+    }
+
+    fn add_unit_types(&mut self, unit_types: Vec<UnitTypeInfo>) {
+        for info in unit_types.into_iter() {
+            if self.get_unit_type_id_opt(&info.name).is_some() {
+                panic!("Duplicate unit type name: \"{}\"", info.name);
+            }
+            if info.weapons.is_empty() {
+                panic!("Unit type \"{}\" has no weapons", info.name);
+            }
+            let mut weapon_type_ids = Vec::new();
+            for weapon in info.weapons.iter() {
+                match self.get_weapon_type_id_opt(weapon) {
+                    Some(id) => weapon_type_ids.push(id),
+                    None => panic!(
+                        "Unit type \"{}\" refers to unknown weapon type \"{}\"",
+                        info.name, weapon
+                    ),
+                }
+            }
+            self.unit_types.push(UnitType {
+                name: info.name,
+                class: info.class,
+                count: info.count,
+                size: info.size,
+                armor: info.armor,
+                toughness: info.toughness,
+                weapon_skill: info.weapon_skill,
+                weapon_type_ids,
+                move_points: info.move_points,
+                sight_range: info.sight_range,
+            });
+        }
+    }
+
+    pub fn unit_types(&self) -> &Vec<UnitType> {
+        &self.unit_types
+    }
+
+    pub fn terrain_types(&self) -> &Vec<TerrainType> {
+        &self.terrain_types
+    }
+
+    pub fn weapon_types(&self) -> &Vec<WeaponType> {
+        &self.weapon_types
+    }
+
+    pub fn is_stopped_by_zone_of_control(&self, class: UnitClass) -> bool {
+        self.zone_of_control_classes.contains(&class)
+    }
+
+    pub fn get_terrain_type(&self, terrain_type_id: TerrainTypeId) -> &TerrainType {
+        &self.terrain_types[terrain_type_id.id as usize]
+    }
+
+    pub fn get_terrain_type_id_by_symbol(&self, symbol: char) -> Option<TerrainTypeId> {
+        for (id, terrain_type) in self.terrain_types.iter().enumerate() {
+            if terrain_type.symbol == symbol {
+                return Some(TerrainTypeId { id: id as MInt });
+            }
+        }
+        None
+    }
+
+    fn get_unit_type_id_opt(&self, name: &str) -> Option<UnitTypeId> {
+        for (id, unit_type) in self.unit_types.iter().enumerate() {
+            if &unit_type.name == name {
+                return Some(UnitTypeId { id: id as MInt });
+            }
+        }
+        None
+    }
+
+    pub fn get_unit_type(&self, unit_type_id: UnitTypeId) -> &UnitType {
+        &self.unit_types[unit_type_id.id as usize]
+    }
+
+    pub fn get_unit_type_id(&self, name: &str) -> UnitTypeId {
+        match self.get_unit_type_id_opt(name) {
+            Some(id) => id,
+            None => panic!("No unit type with name: \"{}\"", name),
+        }
+    }
+
+    pub fn get_weapon_type(&self, weapon_type_id: WeaponTypeId) -> &WeaponType {
+        &self.weapon_types[weapon_type_id.id as usize]
+    }
+
+    // Weapon by its index in unit type's weapons list
+    pub fn get_unit_weapon_type(&self, unit_type_id: UnitTypeId, index: MInt) -> &WeaponType {
+        let unit_type = self.get_unit_type(unit_type_id);
+        self.get_weapon_type(unit_type.weapon_type_ids[index as usize])
+    }
+
+    fn get_weapon_type_id_opt(&self, name: &str) -> Option<WeaponTypeId> {
+        for (id, weapon_type) in self.weapon_types.iter().enumerate() {
+            if &weapon_type.name == name {
+                return Some(WeaponTypeId { id: id as MInt });
+            }
+        }
+        None
+    }
+}
+
+const SAVE_VERSION: MInt = 10;
+
+// Everything that is needed to continue a game
+#[derive(Serialize, Deserialize)]
+struct SaveInfo {
+    version: MInt,
+    game_state: GameState,
+    players: Vec<Player>,
+    current_player_id: PlayerId,
+    // what players have seen so far
+    views: HashMap<PlayerId, GameState>,
+    // events that players have not seen yet
+    event_lists: HashMap<PlayerId, Vec<Event>>,
+    fow: Fow,
+    victory: Victory,
+    game_result: Option<GameResult>,
+    seed: u64,
+    rng: Pcg32,
+    replay: Replay,
+}
+
+pub struct Core {
+    game_state: GameState,
+    players: Vec<Player>,
+    current_player_id: PlayerId,
+    core_event_list: Vec<Event>,
+    event_lists: HashMap<PlayerId, Vec<Event>>,
+    views: HashMap<PlayerId, GameState>,
+    fow: Fow,
+    victory: Victory,
+    game_result: Option<GameResult>,
+    // the same seed and commands always give the same events
+    seed: u64,
+    rng: Pcg32,
+    replay: Replay,
+    object_types: ObjectTypes,
+}
+
+fn get_event_lists(players: &[Player]) -> HashMap<PlayerId, Vec<Event>> {
+    let mut map = HashMap::new();
+    for player in players.iter() {
+        let _ = map.insert(player.id.clone(), Vec::new());
+    }
+    map
+}
+
+fn get_views(players: &[Player], map: &TerrainMap) -> HashMap<PlayerId, GameState> {
+    let mut map_views = HashMap::new();
+    for player in players.iter() {
+        let _ = map_views.insert(player.id.clone(), GameState::new(map.clone()));
+    }
+    map_views
+}
+
+// Recreates everything the player has seen as ShowUnit events
+pub fn get_view_events(view: &GameState) -> Vec<Event> {
+    let mut unit_ids: Vec<&UnitId> = view.units.keys().collect();
+    unit_ids.sort();
+    unit_ids
+        .into_iter()
+        .map(|id| EventShowUnit(view.units[id].clone()))
+        .collect()
+}
+
+fn get_players_list(scenario: &Scenario) -> Vec<Player> {
+    let mut players = Vec::new();
+    for (id, player) in scenario.players.iter().enumerate() {
+        players.push(Player {
+            id: PlayerId { id: id as MInt },
+            name: player.name.clone(),
+            is_ai: player.ai.unwrap_or(false),
+        });
+    }
+    players
+}
+
+fn get_terrain_map(object_types: &ObjectTypes, scenario: &Scenario) -> TerrainMap {
+    match scenario.terrain {
+        Some(ref rows) => TerrainMap::from_rows(object_types, scenario.map_size, rows),
+        None => TerrainMap::new(scenario.map_size, TerrainTypeId { id: 0 }),
+    }
+}
+
+impl Core {
+    // seed from arguments overrides scenario's seed
+    pub fn new(fs: &FileSystem, scenario_path: &Path, seed: Option<u64>) -> Core {
+        // TODO: fix set_error_context
+        // set_error_context!("constructing Core", "-");
+        let scenario = Scenario::new(&fs.get(scenario_path));
+        let object_types = ObjectTypes::new(&fs.get(Path::new("data/object_types.json")));
+        let map = get_terrain_map(&object_types, &scenario);
+        let players = get_players_list(&scenario);
+        let seed = match seed.or(scenario.seed) {
+            Some(seed) => seed,
+            None => rand::thread_rng().gen(),
+        };
+        let replay = Replay::new(seed, map.clone(), players.clone());
+        let mut core = Core {
+            event_lists: get_event_lists(&players),
+            views: get_views(&players, &map),
+            game_state: GameState::new(map),
+            fow: Fow::new(&players),
+            victory: Victory::new(&scenario, &players),
+            game_result: None,
+            players,
+            current_player_id: PlayerId { id: 0 },
+            core_event_list: Vec::new(),
+            seed,
+            rng: Pcg32::seed_from_u64(seed),
+            replay,
+            object_types,
+        };
+        core.get_units(&scenario);
+        core
+    }
+
+    pub fn load(fs: &FileSystem, save_path: &Path) -> Core {
+        let save_path = fs.get(save_path);
+        let save: SaveInfo = match serde_json::from_str(&read_file(&save_path)) {
+            Ok(save) => save,
+            Err(err) => panic!("Can not parse save {}: {}", save_path.display(), err),
+        };
+        if save.version != SAVE_VERSION {
+            panic!(
+                "Save {}: unsupported version {}, expected {}",
+                save_path.display(),
+                save.version,
+                SAVE_VERSION
+            );
+        }
+        let object_types = ObjectTypes::new(&fs.get(Path::new("data/object_types.json")));
+        // visualizer starts from scratch, so every player gets
+        // a snapshot of its view before its pending events
+        let mut event_lists = HashMap::new();
+        for player in save.players.iter() {
+            let mut events = get_view_events(&save.views[&player.id]);
+            events.extend(save.event_lists[&player.id].iter().cloned());
+            // game over screen is shown again if the game has ended
+            if let Some(ref result) = save.game_result {
+                if !events.iter().any(|event| matches!(*event, EventGameOver(_))) {
+                    events.push(EventGameOver(result.clone()));
+                }
+            }
+            let _ = event_lists.insert(player.id.clone(), events);
+        }
+        let views = get_views(&save.players, &save.game_state.map);
+        Core {
+            game_state: save.game_state,
+            players: save.players,
+            current_player_id: save.current_player_id,
+            core_event_list: Vec::new(),
+            event_lists,
+            views,
+            fow: save.fow,
+            victory: save.victory,
+            game_result: save.game_result,
+            seed: save.seed,
+            rng: save.rng,
+            replay: save.replay,
+            object_types,
+        }
+    }
+
+    pub fn save(&self, save_path: &Path) {
+        let save = SaveInfo {
+            version: SAVE_VERSION,
+            game_state: self.game_state.clone(),
+            players: self.players.clone(),
+            current_player_id: self.current_player_id.clone(),
+            views: self.views.clone(),
+            event_lists: self.event_lists.clone(),
+            fow: self.fow.clone(),
+            victory: self.victory.clone(),
+            game_result: self.game_result.clone(),
+            seed: self.seed,
+            rng: self.rng.clone(),
+            replay: self.replay.clone(),
+        };
+        write_file(save_path, &serde_json::to_string_pretty(&save).unwrap());
+    }
+
+    pub fn object_types(&self) -> &ObjectTypes {
+        &self.object_types
+    }
+
+    pub fn players(&self) -> &Vec<Player> {
+        &self.players
+    }
+
+    pub fn max_turns(&self) -> Option<MInt> {
+        self.victory.max_turns()
+    }
+
+    pub fn game_result(&self) -> Option<&GameResult> {
+        self.game_result.as_ref()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    fn get_units(&mut self, scenario: &Scenario) {
+        for unit in scenario.units.iter() {
+            let type_id = self.object_types.get_unit_type_id(&unit.type_name);
+            let player_id = PlayerId { id: unit.player };
+            self.add_unit(unit.pos, type_id, player_id);
+        }
+    }
+
+    fn get_new_unit_id(&self) -> UnitId {
+        let id = match self.game_state.units.keys().max_by_key(|&n| n) {
+            Some(n) => n.id + 1,
+            None => 0,
+        };
+        UnitId { id }
+    }
+
+    fn add_unit(&mut self, pos: MapPos, type_id: UnitTypeId, player_id: PlayerId) {
+        let event = EventCreateUnit(self.get_new_unit_id(), pos, type_id, player_id);
+        self.do_core_event(event);
+    }
+
+    pub fn map_size(&self) -> Size2<MInt> {
+        self.game_state.map.get_size()
+    }
+
+    pub fn map(&self) -> &TerrainMap {
+        &self.game_state.map
+    }
+
+    pub fn get_weapon_type(&self, weapon_type_id: WeaponTypeId) -> &WeaponType {
+        self.object_types.get_weapon_type(weapon_type_id)
+    }
+
+    fn attack(
+        &mut self,
+        attacker_id: UnitId,
+        defender_id: UnitId,
+        weapon_index: MInt,
+    ) -> AttackResult {
+        // fields are borrowed one by one: rng is mutable
+        let attacker = &self.game_state.units[&attacker_id];
+        let defender = &self.game_state.units[&defender_id];
+        combat::attack(
+            &mut self.rng,
+            &self.object_types,
+            attacker,
+            defender,
+            weapon_index,
+        )
+    }
+
+    pub fn player_id(&self) -> PlayerId {
+        self.current_player_id.clone()
+    }
+
+    pub fn get_event(&mut self) -> Option<Event> {
+        let player_id = self.current_player_id.clone();
+        self.get_player_event(&player_id)
+    }
+
+    // Server sends events to every player, not only to the current one
+    pub fn get_player_event(&mut self, player_id: &PlayerId) -> Option<Event> {
+        let list = self.event_lists.get_mut(player_id).unwrap();
+        if list.is_empty() {
+            None
+        } else {
+            let event = list.remove(0);
+            let view = self.views.get_mut(player_id).unwrap();
+            view.apply_event(&self.object_types, &event);
+            Some(event)
+        }
+    }
+
+    // What the player has seen: state after all taken events
+    pub fn view(&self, player_id: &PlayerId) -> &GameState {
+        &self.views[player_id]
+    }
+
+    fn is_player_alive(&self, player_id: &PlayerId) -> bool {
+        self.game_state
+            .units
+            .values()
+            .any(|unit| unit.player_id == *player_id)
+    }
+
+    // Players without units are eliminated and skipped
+    fn get_next_player_id(&self) -> PlayerId {
+        let players_count = self.players.len() as MInt;
+        for i in 1..players_count + 1 {
+            let id = PlayerId {
+                id: (self.current_player_id.id + i) % players_count,
+            };
+            if self.is_player_alive(&id) {
+                return id;
+            }
+        }
+        self.current_player_id.clone()
+    }
+
+    fn get_own_unit(&self, id: UnitId) -> Result<&Unit, CommandError> {
+        match self.game_state.units.get(&id) {
+            Some(unit) if unit.player_id == self.current_player_id => Ok(unit),
+            Some(_) => Err(CommandError::NotOwnUnit),
+            None => Err(CommandError::NoSuchUnit),
+        }
+    }
+
+    fn check_path(&self, unit: &Unit, path: &[MapPos]) -> Result<(), CommandError> {
+        let state = self
+            .fow
+            .get_player_state(&self.game_state, &self.current_player_id);
+        if unit.overwatch {
+            return Err(CommandError::OnOverwatch);
+        }
+        if unit.move_points == 0 {
+            return Err(CommandError::NoMovePoints);
+        }
+        if path.len() < 2 || path[0] != unit.pos {
+            return Err(CommandError::BadPath);
+        }
+        let mut cost = 0;
+        for window in path.windows(2) {
+            let pos = window[1];
+            if !state.map.is_inboard(pos) || distance(window[0], pos) != 1 {
+                return Err(CommandError::BadPath);
+            }
+            // only the start and the end of the path can be next to enemies
+            if window[0] != path[0]
+                && is_move_stopped_at(&self.object_types, &state, unit, window[0])
+            {
+                return Err(CommandError::ZoneOfControl);
+            }
+            if !state.units_at(pos).is_empty() {
+                return Err(CommandError::TileIsOccupied);
+            }
+            cost += match get_move_cost(&self.object_types, &state, unit, pos) {
+                Some(move_cost) => move_cost,
+                None => return Err(CommandError::TileIsImpassable),
+            };
+        }
+        if cost > unit.move_points {
+            return Err(CommandError::NotEnoughMovePoints);
+        }
+        Ok(())
+    }
+
+    // Returns index of the weapon that fires.
+    // Units hidden from the player do not block line of sight here.
+    fn check_attack(
+        &self,
+        attacker_id: UnitId,
+        defender_id: UnitId,
+        weapon_index: Option<MInt>,
+    ) -> Result<MInt, CommandError> {
+        let attacker = self.get_own_unit(attacker_id)?;
+        let state = self
+            .fow
+            .get_player_state(&self.game_state, &self.current_player_id);
+        let defender = match state.units.get(&defender_id) {
+            Some(defender) => defender,
+            None if self.game_state.units.contains_key(&defender_id) => {
+                return Err(CommandError::TargetIsNotVisible)
+            }
+            None => return Err(CommandError::NoSuchUnit),
+        };
+        if defender.player_id == self.current_player_id {
+            return Err(CommandError::CanNotAttackOwnUnit);
+        }
+        if attacker.attacked {
+            return Err(CommandError::AlreadyAttacked);
+        }
+        if attacker.overwatch {
+            return Err(CommandError::OnOverwatch);
+        }
+        let weapon_type_ids = &self.object_types.get_unit_type(attacker.type_id).weapon_type_ids;
+        match weapon_index {
+            Some(index) if index < 0 || index >= weapon_type_ids.len() as MInt => {
+                Err(CommandError::NoSuchWeapon)
+            }
+            Some(index) => {
+                check_line_of_fire(
+                    &self.object_types,
+                    &state,
+                    attacker,
+                    index,
+                    defender.pos,
+                )?;
+                Ok(index)
+            }
+            None => match get_best_weapon_index(
+                &self.object_types,
+                &state,
+                attacker,
+                defender,
+            ) {
+                Some(index) => Ok(index),
+                None => {
+                    // line of sight is the same for all weapons
+                    let max_distance = weapon_type_ids
+                        .iter()
+                        .map(|&id| self.object_types.get_weapon_type(id).max_distance)
+                        .max()
+                        .unwrap();
+                    if distance(attacker.pos, defender.pos) > max_distance {
+                        Err(CommandError::OutOfRange)
+                    } else {
+                        Err(CommandError::NoLineOfSight)
+                    }
+                }
+            },
+        }
+    }
+
+    fn check_overwatch(&self, unit_id: UnitId) -> Result<(), CommandError> {
+        let unit = self.get_own_unit(unit_id)?;
+        if unit.attacked {
+            return Err(CommandError::AlreadyAttacked);
+        }
+        if unit.overwatch {
+            return Err(CommandError::OnOverwatch);
+        }
+        Ok(())
+    }
+
+    fn check_create_unit(&self, pos: MapPos) -> Result<(), CommandError> {
+        if !self.game_state.map.is_inboard(pos) {
+            return Err(CommandError::OutOfMap);
+        }
+        if !self.game_state.units_at(pos).is_empty() {
+            return Err(CommandError::TileIsOccupied);
+        }
+        Ok(())
+    }
+
+    fn command_to_event(&mut self, command: Command) -> Result<Event, CommandError> {
+        if self.game_result.is_some() {
+            return Err(CommandError::GameIsOver);
+        }
+        match command {
+            Command::CommandEndTurn => Ok(EventEndTurn(
+                self.current_player_id.clone(),
+                self.get_next_player_id(),
+            )),
+            Command::CommandCreateUnit(pos) => {
+                self.check_create_unit(pos)?;
+                Ok(EventCreateUnit(
+                    self.get_new_unit_id(),
+                    pos,
+                    self.object_types.get_unit_type_id("soldier"),
+                    self.current_player_id.clone(),
+                ))
+            }
+            Command::CommandMove(unit_id, path) => {
+                self.check_path(self.get_own_unit(unit_id)?, &path)?;
+                Ok(EventMove(unit_id, path))
+            }
+            Command::CommandAttackUnit(attacker_id, defender_id, weapon_index) => {
+                let weapon_index = self.check_attack(attacker_id, defender_id, weapon_index)?;
+                let result = self.attack(attacker_id, defender_id, weapon_index);
+                Ok(EventAttackUnit(attacker_id, defender_id, result))
+            }
+            Command::CommandOverwatch(unit_id) => {
+                self.check_overwatch(unit_id)?;
+                Ok(EventOverwatch(unit_id))
+            }
+        }
+    }
+
+    // Invalid commands are rejected without any changes
+    pub fn do_command(&mut self, command: Command) -> Result<(), CommandError> {
+        let event = self.command_to_event(command.clone())?;
+        self.replay.log.push(ReplayEntry::ReplayCommand(command));
+        match event {
+            EventMove(unit_id, path) => self.do_move(unit_id, &path),
+            event => self.do_core_event(event),
+        }
+        Ok(())
+    }
+
+    // Moves are done one step at a time, so enemies on overwatch
+    // can fire at the unit in every tile of its path. Enemies that were
+    // hidden when the path was checked can stop it with their zones of control
+    // or by standing in its way.
+    fn do_move(&mut self, unit_id: UnitId, path: &[MapPos]) {
+        for step in path.windows(2) {
+            if !self.game_state.units_at(step[1]).is_empty() {
+                return;
+            }
+            self.do_core_event(EventMove(unit_id, step.to_vec()));
+            if self.do_reaction_fire(unit_id) {
+                return;
+            }
+            let unit = &self.game_state.units[&unit_id];
+            if is_move_stopped_at(&self.object_types, &self.game_state, unit, unit.pos) {
+                return;
+            }
+        }
+    }
+
+    // Overwatching enemies that see the unit and have it in line of fire
+    // attack it in order of ids. Returns true if the move is interrupted:
+    // the unit is destroyed or the game is over.
+    fn do_reaction_fire(&mut self, unit_id: UnitId) -> bool {
+        let player_id = self.game_state.units[&unit_id].player_id.clone();
+        let mut watcher_ids: Vec<UnitId> = self
+            .game_state
+            .units
+            .values()
+            .filter(|unit| unit.overwatch && unit.player_id != player_id)
+            .map(|unit| unit.id)
+            .collect();
+        watcher_ids.sort();
+        for watcher_id in watcher_ids.into_iter() {
+            let watcher = &self.game_state.units[&watcher_id];
+            let unit = &self.game_state.units[&unit_id];
+            if !is_visible(
+                &self.object_types,
+                &self.game_state,
+                &watcher.player_id,
+                unit.pos,
+            ) {
+                continue;
+            }
+            let weapon_index =
+                match get_best_weapon_index(&self.object_types, &self.game_state, watcher, unit) {
+                    Some(weapon_index) => weapon_index,
+                    None => continue,
+                };
+            let result = self.attack(watcher_id, unit_id, weapon_index);
+            let is_destroyed = result.is_destroyed(&self.game_state.units[&unit_id]);
+            self.do_core_event(EventAttackUnit(watcher_id, unit_id, result));
+            if is_destroyed || self.game_result.is_some() {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn do_core_event(&mut self, core_event: Event) {
+        self.core_event_list.push(core_event);
+        self.make_events();
+    }
+
+    fn apply_event(&mut self, event: &Event) {
+        match event {
+            EventEndTurn(old_player_id, new_player_id) => {
+                for player in self.players.iter() {
+                    if player.id == *new_player_id {
+                        if self.current_player_id == *old_player_id {
+                            self.current_player_id = player.id.clone();
+                        }
+                        return;
+                    }
+                }
+            }
+            EventGameOver(result) => {
+                self.game_result = Some(result.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn make_events(&mut self) {
+        let player_ids: Vec<PlayerId> = self.players.iter().map(|p| p.id.clone()).collect();
+        while self.core_event_list.len() != 0 {
+            let event = self.core_event_list.pop().unwrap();
+            let game_result = self.victory.process_event(&self.game_state, &event);
+            // filtering is done against the state before the event
+            let mut player_events = Vec::new();
+            for player_id in player_ids.iter() {
+                player_events.push(self.fow.filter_event(
+                    &self.object_types,
+                    &self.game_state,
+                    player_id,
+                    &event,
+                ));
+            }
+            self.apply_event(&event);
+            self.game_state.apply_event(&self.object_types, &event);
+            self.replay
+                .log
+                .push(ReplayEntry::ReplayEvent(event.clone()));
+            for (player_id, events) in player_ids.iter().zip(player_events) {
+                let fow_events = self
+                    .fow
+                    .update(&self.object_types, &self.game_state, player_id);
+                let event_list = self.event_lists.get_mut(player_id).unwrap();
+                event_list.extend(events);
+                event_list.extend(fow_events);
+            }
+            if let Some(result) = game_result {
+                self.core_event_list.push(EventGameOver(result));
+            }
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -1,7 +1,8 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::dir::Dir::{East, NorthEast, NorthWest, SouthEast, SouthWest, West};
-use crate::core::types::{MInt, MapPos};
+use crate::dir::Dir::{East, NorthEast, NorthWest, SouthEast, SouthWest, West};
+use crate::hex;
+use crate::types::{MInt, MapPos};
 use cgmath::Vector2;
 
 #[derive(Copy, Clone)]
@@ -14,7 +15,7 @@ pub enum Dir {
     NorthWest,
 }
 
-const DIR_TO_POS_DIFF: [[Vector2<MInt>; 6]; 2] = [
+pub const DIR_TO_POS_DIFF: [[Vector2<MInt>; 6]; 2] = [
     [
         Vector2 { x: 1, y: -1 },
         Vector2 { x: 1, y: 0 },
@@ -63,14 +64,7 @@ impl Dir {
     }
 
     pub fn get_neighbour_pos(pos: MapPos, dir: Dir) -> MapPos {
-        let is_odd_row = pos.v.y % 2 == 1;
-        let subtable_index = if is_odd_row { 1 } else { 0 };
-        let direction_index = dir.to_int();
-        assert!(direction_index >= 0 && direction_index < 6);
-        let difference = DIR_TO_POS_DIFF[subtable_index][direction_index as usize];
-        MapPos {
-            v: pos.v + difference,
-        }
+        hex::neighbour(pos, dir)
     }
 }
 
@@ -0,0 +1,222 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::Event::{
+    EventAttackUnit, EventCreateUnit, EventEndTurn, EventGameOver, EventHideUnit, EventMove,
+    EventOverwatch, EventShowUnit,
+};
+use crate::core::{Event, ObjectTypes, Player, Unit};
+use crate::game_state::GameState;
+use crate::map::distance;
+use crate::types::{MapPos, PlayerId, UnitId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub fn is_visible(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    player_id: &PlayerId,
+    pos: MapPos,
+) -> bool {
+    for (_, unit) in state.units.iter() {
+        if unit.player_id != *player_id {
+            continue;
+        }
+        let sight_range = object_types.get_unit_type(unit.type_id).sight_range;
+        if distance(unit.pos, pos) <= sight_range {
+            return true;
+        }
+    }
+    false
+}
+
+fn show_unit_at(unit: &Unit, pos: MapPos) -> Event {
+    let mut unit = unit.clone();
+    unit.pos = pos;
+    EventShowUnit(unit)
+}
+
+// Tracks which enemy units every player currently sees
+// and rewrites core events into per player events.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Fow {
+    visible_units: HashMap<PlayerId, HashSet<UnitId>>,
+}
+
+impl Fow {
+    pub fn new(players: &[Player]) -> Fow {
+        let mut visible_units = HashMap::new();
+        for player in players.iter() {
+            let _ = visible_units.insert(player.id.clone(), HashSet::new());
+        }
+        Fow { visible_units }
+    }
+
+    // State without the enemy units that the player does not see.
+    // Commands are checked against it, so errors do not reveal hidden units.
+    pub fn get_player_state(&self, state: &GameState, player_id: &PlayerId) -> GameState {
+        let mut player_state = state.clone();
+        player_state.units.retain(|id, unit| {
+            unit.player_id == *player_id || self.visible_units[player_id].contains(id)
+        });
+        player_state
+    }
+
+    fn is_unit_known(&self, state: &GameState, player_id: &PlayerId, unit_id: UnitId) -> bool {
+        state.units[&unit_id].player_id == *player_id
+            || self.visible_units[player_id].contains(&unit_id)
+    }
+
+    fn set_unit_known(&mut self, player_id: &PlayerId, unit_id: UnitId, is_known: bool) {
+        let units = self.visible_units.get_mut(player_id).unwrap();
+        if is_known {
+            let _ = units.insert(unit_id);
+        } else {
+            let _ = units.remove(&unit_id);
+        }
+    }
+
+    // Splits enemy's path into visible parts
+    fn filter_move(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        player_id: &PlayerId,
+        unit_id: UnitId,
+        path: &[MapPos],
+    ) -> Vec<Event> {
+        let unit = &state.units[&unit_id];
+        let mut events = Vec::new();
+        let mut is_shown = self.is_unit_known(state, player_id, unit_id);
+        let mut visible_path = Vec::new();
+        for &pos in path.iter() {
+            let is_pos_visible = is_visible(object_types, state, player_id, pos);
+            if is_pos_visible {
+                if !is_shown {
+                    events.push(show_unit_at(unit, pos));
+                    is_shown = true;
+                }
+                visible_path.push(pos);
+            } else if is_shown {
+                if visible_path.len() > 1 {
+                    events.push(EventMove(unit_id, visible_path.clone()));
+                }
+                events.push(EventHideUnit(unit_id));
+                visible_path.clear();
+                is_shown = false;
+            }
+        }
+        if is_shown && visible_path.len() > 1 {
+            events.push(EventMove(unit_id, visible_path));
+        }
+        self.set_unit_known(player_id, unit_id, is_shown);
+        events
+    }
+
+    // Attacker is revealed to the defender and vice versa
+    fn filter_attack(
+        &mut self,
+        state: &GameState,
+        player_id: &PlayerId,
+        attacker_id: UnitId,
+        defender_id: UnitId,
+        event: &Event,
+    ) -> Vec<Event> {
+        let is_attacker_known = self.is_unit_known(state, player_id, attacker_id);
+        let is_defender_known = self.is_unit_known(state, player_id, defender_id);
+        if !is_attacker_known && !is_defender_known {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        for &(unit_id, is_known) in [
+            (attacker_id, is_attacker_known),
+            (defender_id, is_defender_known),
+        ]
+        .iter()
+        {
+            if !is_known {
+                let unit = &state.units[&unit_id];
+                events.push(show_unit_at(unit, unit.pos));
+                self.set_unit_known(player_id, unit_id, true);
+            }
+        }
+        events.push(event.clone());
+        events
+    }
+
+    // Must be called before the event is applied to the state
+    pub fn filter_event(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        player_id: &PlayerId,
+        event: &Event,
+    ) -> Vec<Event> {
+        match *event {
+            EventEndTurn(_, _) | EventGameOver(_) => vec![event.clone()],
+            EventCreateUnit(id, pos, _, ref owner_id) => {
+                if *owner_id == *player_id {
+                    vec![event.clone()]
+                } else if is_visible(object_types, state, player_id, pos) {
+                    self.set_unit_known(player_id, id, true);
+                    vec![event.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+            EventMove(unit_id, ref path) => {
+                if state.units[&unit_id].player_id == *player_id {
+                    vec![event.clone()]
+                } else {
+                    self.filter_move(object_types, state, player_id, unit_id, path)
+                }
+            }
+            EventAttackUnit(attacker_id, defender_id, _) => {
+                self.filter_attack(state, player_id, attacker_id, defender_id, event)
+            }
+            // enemies that see the unit see it taking aim
+            EventOverwatch(unit_id) => {
+                if self.is_unit_known(state, player_id, unit_id) {
+                    vec![event.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+            EventShowUnit(_) | EventHideUnit(_) => panic!("Not a core event"),
+        }
+    }
+
+    // Must be called after the event is applied to the state.
+    // Returns events for units that came into or left player's view.
+    pub fn update(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        player_id: &PlayerId,
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        // units killed by the event are removed by the event itself
+        self.visible_units
+            .get_mut(player_id)
+            .unwrap()
+            .retain(|id| state.units.contains_key(id));
+        let mut unit_ids: Vec<UnitId> = state.units.keys().cloned().collect();
+        unit_ids.sort();
+        for unit_id in unit_ids.into_iter() {
+            let unit = &state.units[&unit_id];
+            if unit.player_id == *player_id {
+                continue;
+            }
+            let is_known = self.visible_units[player_id].contains(&unit_id);
+            let is_unit_visible = is_visible(object_types, state, player_id, unit.pos);
+            if is_unit_visible && !is_known {
+                events.push(EventShowUnit(unit.clone()));
+            } else if !is_unit_visible && is_known {
+                events.push(EventHideUnit(unit_id));
+            }
+            self.set_unit_known(player_id, unit_id, is_unit_visible);
+        }
+        events
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
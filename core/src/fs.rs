@@ -13,6 +13,13 @@ impl FileSystem {
         }
     }
 
+    // For tools and tests that are not started from the game directory
+    pub fn with_root(root_path: &Path) -> FileSystem {
+        FileSystem {
+            root_path: root_path.to_path_buf(),
+        }
+    }
+
     pub fn get(&self, path: &Path) -> PathBuf {
         self.root_path.join(path)
     }
@@ -0,0 +1,60 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::{Command, CommandError, Core, Event, ObjectTypes, Player};
+use crate::map::TerrainMap;
+use crate::types::{MInt, PlayerId, Size2};
+
+// What frontends play with: local Core or connection to a server
+pub trait Game {
+    fn object_types(&self) -> &ObjectTypes;
+    fn players(&self) -> &Vec<Player>;
+    fn map(&self) -> &TerrainMap;
+    // Player whose view is shown and whose events are returned
+    fn player_id(&self) -> PlayerId;
+    fn current_player_id(&self) -> PlayerId;
+    fn get_event(&mut self) -> Option<Event>;
+    fn do_command(&mut self, command: Command) -> Result<(), CommandError>;
+    // None if the game is played on a server
+    fn core(&self) -> Option<&Core>;
+
+    fn map_size(&self) -> Size2<MInt> {
+        self.map().get_size()
+    }
+}
+
+// Hotseat: view always follows the current player
+impl Game for Core {
+    fn object_types(&self) -> &ObjectTypes {
+        Core::object_types(self)
+    }
+
+    fn players(&self) -> &Vec<Player> {
+        Core::players(self)
+    }
+
+    fn map(&self) -> &TerrainMap {
+        Core::map(self)
+    }
+
+    fn player_id(&self) -> PlayerId {
+        Core::player_id(self)
+    }
+
+    fn current_player_id(&self) -> PlayerId {
+        Core::player_id(self)
+    }
+
+    fn get_event(&mut self) -> Option<Event> {
+        Core::get_event(self)
+    }
+
+    fn do_command(&mut self, command: Command) -> Result<(), CommandError> {
+        Core::do_command(self, command)
+    }
+
+    fn core(&self) -> Option<&Core> {
+        Some(self)
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -1,17 +1,23 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::core::{Event, ObjectTypes, Unit};
-use crate::core::types::{MapPos, PlayerId, UnitId};
+use crate::core::{Event, ObjectTypes, Unit};
+use crate::map::TerrainMap;
+use crate::pathfinder::get_path_cost;
+use crate::types::{MapPos, PlayerId, UnitId};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub units: HashMap<UnitId, Unit>,
+    pub map: TerrainMap,
 }
 
 impl<'a> GameState {
-    pub fn new() -> GameState {
+    pub fn new(map: TerrainMap) -> GameState {
         GameState {
             units: HashMap::new(),
+            map,
         }
     }
 
@@ -30,6 +36,7 @@ impl<'a> GameState {
             if unit.player_id == player_id {
                 unit.move_points = object_types.get_unit_type(unit.type_id).move_points;
                 unit.attacked = false;
+                unit.overwatch = false;
             }
         }
     }
@@ -38,17 +45,18 @@ impl<'a> GameState {
         match event {
             Event::EventMove(id, ref path) => {
                 let pos = *path.last().unwrap();
+                let cost = get_path_cost(object_types, self, &self.units[&id], path);
                 let unit = self.units.get_mut(&id).unwrap();
                 unit.pos = pos;
-                assert!(unit.move_points > 0);
-                unit.move_points = 0;
+                assert!(unit.move_points >= cost);
+                unit.move_points -= cost;
             }
             Event::EventEndTurn(_, new_player_id) => {
                 self.refresh_units(object_types, new_player_id.clone());
             }
             Event::EventCreateUnit(id, pos, type_id, player_id) => {
                 assert!(self.units.get(&id).is_none());
-                let move_points = object_types.get_unit_type(type_id.clone()).move_points;
+                let unit_type = object_types.get_unit_type(type_id.clone());
                 let _ = self.units.insert(
                     id.clone(),
                     Unit {
@@ -56,20 +64,40 @@ impl<'a> GameState {
                         pos: pos.clone(),
                         player_id: player_id.clone(),
                         type_id: type_id.clone(),
-                        move_points: move_points.clone(),
+                        move_points: unit_type.move_points,
                         attacked: false,
+                        overwatch: false,
+                        count: unit_type.count,
                     },
                 );
             }
-            Event::EventAttackUnit(attacker_id, defender_id, killed) => {
-                if *killed {
-                    assert!(self.units.get(&defender_id).is_some());
+            Event::EventAttackUnit(attacker_id, defender_id, ref result) => {
+                if result.is_destroyed(&self.units[&defender_id]) {
                     let _ = self.units.remove(&defender_id).unwrap();
+                } else {
+                    let defender = self.units.get_mut(&defender_id).unwrap();
+                    defender.count -= result.killed_count();
                 }
                 let unit = self.units.get_mut(&attacker_id).unwrap();
                 assert!(!unit.attacked);
                 unit.attacked = true;
+                // reaction fire uses the reserved attack
+                unit.overwatch = false;
             }
+            Event::EventOverwatch(id) => {
+                let unit = self.units.get_mut(id).unwrap();
+                assert!(!unit.attacked && !unit.overwatch);
+                unit.overwatch = true;
+            }
+            Event::EventShowUnit(ref unit) => {
+                assert!(self.units.get(&unit.id).is_none());
+                let _ = self.units.insert(unit.id, unit.clone());
+            }
+            Event::EventHideUnit(id) => {
+                assert!(self.units.get(&id).is_some());
+                let _ = self.units.remove(&id).unwrap();
+            }
+            Event::EventGameOver(_) => {}
         }
     }
 }
@@ -0,0 +1,204 @@
+// See LICENSE file for copyright and license details.
+
+// Hex grid math. Map uses "even-r" offset coordinates: pointy-top hexes,
+// even rows are shifted right by half a tile. Offset coordinates are
+// converted to cube ones (x + y + z == 0) where most things are simple.
+
+use crate::dir::Dir;
+use crate::types::{MInt, MapPos};
+use cgmath::Vector2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CubePos {
+    pub x: MInt,
+    pub y: MInt,
+    pub z: MInt,
+}
+
+// Same order as Dir: NorthEast, East, SouthEast, SouthWest, West, NorthWest
+const DIR_TO_CUBE_DIFF: [CubePos; 6] = [
+    CubePos { x: 1, y: 0, z: -1 },
+    CubePos { x: 1, y: -1, z: 0 },
+    CubePos { x: 0, y: -1, z: 1 },
+    CubePos { x: -1, y: 0, z: 1 },
+    CubePos { x: -1, y: 1, z: 0 },
+    CubePos { x: 0, y: 1, z: -1 },
+];
+
+impl CubePos {
+    pub fn from_map_pos(pos: MapPos) -> CubePos {
+        let (col, row) = (pos.v.x, pos.v.y);
+        // row + (row & 1) is even, so the division is exact for negative rows too
+        let x = col - (row + (row & 1)) / 2;
+        let z = row;
+        CubePos { x, y: -x - z, z }
+    }
+
+    pub fn to_map_pos(&self) -> MapPos {
+        let row = self.z;
+        let col = self.x + (row + (row & 1)) / 2;
+        MapPos {
+            v: Vector2 { x: col, y: row },
+        }
+    }
+
+    pub fn from_dir(dir: Dir) -> CubePos {
+        DIR_TO_CUBE_DIFF[dir.to_int() as usize]
+    }
+
+    pub fn add(&self, other: CubePos) -> CubePos {
+        CubePos {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+
+    pub fn sub(&self, other: CubePos) -> CubePos {
+        CubePos {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+
+    pub fn scale(&self, n: MInt) -> CubePos {
+        CubePos {
+            x: self.x * n,
+            y: self.y * n,
+            z: self.z * n,
+        }
+    }
+
+    pub fn distance(&self, other: CubePos) -> MInt {
+        let diff = self.sub(other);
+        (diff.x.abs() + diff.y.abs() + diff.z.abs()) / 2
+    }
+
+    // One step (60 degrees) clockwise around the origin
+    pub fn rotate_clockwise(&self) -> CubePos {
+        CubePos {
+            x: -self.z,
+            y: -self.x,
+            z: -self.y,
+        }
+    }
+
+    // Nearest hex to a point with fractional cube coordinates
+    fn round(x: f64, y: f64, z: f64) -> CubePos {
+        let (mut rx, mut ry, mut rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        if dx > dy && dx > dz {
+            rx = -ry - rz;
+        } else if dy > dz {
+            ry = -rx - rz;
+        } else {
+            rz = -rx - ry;
+        }
+        CubePos {
+            x: rx as MInt,
+            y: ry as MInt,
+            z: rz as MInt,
+        }
+    }
+}
+
+pub fn distance(from: MapPos, to: MapPos) -> MInt {
+    CubePos::from_map_pos(from).distance(CubePos::from_map_pos(to))
+}
+
+pub fn neighbour(pos: MapPos, dir: Dir) -> MapPos {
+    CubePos::from_map_pos(pos)
+        .add(CubePos::from_dir(dir))
+        .to_map_pos()
+}
+
+// In Dir order
+pub fn neighbours(pos: MapPos) -> Vec<MapPos> {
+    (0..6).map(|i| neighbour(pos, Dir::from_int(i))).collect()
+}
+
+// Tiles at exactly this distance, clockwise from the west corner
+pub fn ring(center: MapPos, radius: MInt) -> Vec<MapPos> {
+    assert!(radius >= 0);
+    if radius == 0 {
+        return vec![center];
+    }
+    let mut tiles = Vec::new();
+    let west = CubePos::from_dir(Dir::West);
+    let mut cube = CubePos::from_map_pos(center).add(west.scale(radius));
+    for i in 0..6 {
+        // West corner, then walk along the sides: NE, E, SE, SW, W, NW
+        let side = CubePos::from_dir(Dir::from_int(i));
+        for _ in 0..radius {
+            tiles.push(cube.to_map_pos());
+            cube = cube.add(side);
+        }
+    }
+    tiles
+}
+
+// Center first, then rings of growing radius
+pub fn spiral(center: MapPos, radius: MInt) -> Vec<MapPos> {
+    let mut tiles = Vec::new();
+    for r in 0..radius + 1 {
+        tiles.extend(ring(center, r));
+    }
+    tiles
+}
+
+// Tiles within the distance, row by row
+pub fn range(center: MapPos, radius: MInt) -> Vec<MapPos> {
+    assert!(radius >= 0);
+    let center = CubePos::from_map_pos(center);
+    let mut tiles = Vec::new();
+    for z in -radius..radius + 1 {
+        let min_x = std::cmp::max(-radius, -z - radius);
+        let max_x = std::cmp::min(radius, -z + radius);
+        for x in min_x..max_x + 1 {
+            let diff = CubePos { x, y: -x - z, z };
+            tiles.push(center.add(diff).to_map_pos());
+        }
+    }
+    tiles
+}
+
+// Moves the point off the edges between hexes, so lines along
+// the edges always pick tiles on the same side
+fn nudge(cube: CubePos) -> (f64, f64, f64) {
+    let e = 1e-6;
+    (
+        cube.x as f64 + e,
+        cube.y as f64 + e,
+        cube.z as f64 - e * 2.0,
+    )
+}
+
+// Straight line including both ends, every next tile is a neighbour
+pub fn line(from: MapPos, to: MapPos) -> Vec<MapPos> {
+    let a = CubePos::from_map_pos(from);
+    let b = CubePos::from_map_pos(to);
+    let n = a.distance(b);
+    let (ax, ay, az) = nudge(a);
+    let (bx, by, bz) = nudge(b);
+    let mut tiles = Vec::new();
+    for i in 0..n + 1 {
+        let t = if n == 0 { 0.0 } else { i as f64 / n as f64 };
+        let cube = CubePos::round(ax + (bx - ax) * t, ay + (by - ay) * t, az + (bz - az) * t);
+        tiles.push(cube.to_map_pos());
+    }
+    tiles
+}
+
+// Rotates pos around center by 60 degrees per step, clockwise
+// for positive steps (as Dir goes) and counterclockwise for negative
+pub fn rotate(pos: MapPos, center: MapPos, steps: MInt) -> MapPos {
+    let center_cube = CubePos::from_map_pos(center);
+    let mut diff = CubePos::from_map_pos(pos).sub(center_cube);
+    for _ in 0..steps.rem_euclid(6) {
+        diff = diff.rotate_clockwise();
+    }
+    center_cube.add(diff).to_map_pos()
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,30 @@
+// See LICENSE file for copyright and license details.
+
+// Game rules without graphics: used by the visualizer, tools and tests
+
+#![warn(non_upper_case_globals)]
+#![warn(unused_results)]
+
+pub mod ai;
+pub mod combat;
+pub mod conf;
+pub mod core;
+pub mod dir;
+pub mod fow;
+pub mod fs;
+pub mod game;
+pub mod game_state;
+pub mod hex;
+pub mod los;
+pub mod map;
+pub mod misc;
+pub mod net;
+pub mod pathfinder;
+pub mod replay;
+pub mod scenario;
+pub mod server;
+pub mod types;
+pub mod victory;
+pub mod zoc;
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
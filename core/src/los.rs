@@ -0,0 +1,74 @@
+// See LICENSE file for copyright and license details.
+
+// Line of sight goes along the hex line between two tiles. It is
+// blocked by terrain obstacles and units on the tiles in between,
+// unless the ends are high enough to look over them.
+
+use crate::core::{CommandError, ObjectTypes, Unit};
+use crate::game_state::GameState;
+use crate::hex;
+use crate::map::distance;
+use crate::types::{MInt, MapPos};
+
+// Units look from one level above the ground
+fn get_eye_height(object_types: &ObjectTypes, state: &GameState, pos: MapPos) -> MInt {
+    let terrain_type_id = state.map.terrain_type_id(pos);
+    object_types.get_terrain_type(terrain_type_id).elevation + 1
+}
+
+// Obstacles and units take one level above the ground
+fn get_obstacle_height(object_types: &ObjectTypes, state: &GameState, pos: MapPos) -> MInt {
+    let terrain_type_id = state.map.terrain_type_id(pos);
+    let terrain_type = object_types.get_terrain_type(terrain_type_id);
+    if terrain_type.blocks_sight || !state.units_at(pos).is_empty() {
+        terrain_type.elevation + 1
+    } else {
+        terrain_type.elevation
+    }
+}
+
+pub fn is_line_of_sight_clear(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    from: MapPos,
+    to: MapPos,
+) -> bool {
+    let line = hex::line(from, to);
+    let n = line.len() as MInt - 1;
+    let from_height = get_eye_height(object_types, state, from);
+    let to_height = get_eye_height(object_types, state, to);
+    for (i, &pos) in line.iter().enumerate().take(line.len() - 1).skip(1) {
+        // line near the board edge can go out of it for a tile
+        if !state.map.is_inboard(pos) {
+            continue;
+        }
+        // sight line height here is from_height + (to_height - from_height) * i / n,
+        // both sides are multiplied by n to stay in integers
+        let i = i as MInt;
+        let sight_height = from_height * (n - i) + to_height * i;
+        if get_obstacle_height(object_types, state, pos) * n >= sight_height {
+            return false;
+        }
+    }
+    true
+}
+
+// Target tile must be in weapon range and in line of sight
+pub fn check_line_of_fire(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    attacker: &Unit,
+    weapon_index: MInt,
+    pos: MapPos,
+) -> Result<(), CommandError> {
+    let weapon_type = object_types.get_unit_weapon_type(attacker.type_id, weapon_index);
+    if distance(attacker.pos, pos) > weapon_type.max_distance {
+        return Err(CommandError::OutOfRange);
+    }
+    if !is_line_of_sight_clear(object_types, state, attacker.pos, pos) {
+        return Err(CommandError::NoLineOfSight);
+    }
+    Ok(())
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,92 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::{ObjectTypes, TerrainTypeId};
+use crate::hex;
+use crate::types::{MInt, MapPos, Size2};
+use cgmath::{Vector, Vector2};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone)]
+pub struct MapPosIter {
+    cursor: MapPos,
+    map_size: Size2<MInt>,
+}
+
+impl MapPosIter {
+    pub fn new(map_size: Size2<MInt>) -> MapPosIter {
+        MapPosIter {
+            cursor: MapPos { v: Vector2::zero() },
+            map_size,
+        }
+    }
+}
+
+impl Iterator for MapPosIter {
+    type Item = MapPos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_pos = if self.cursor.v.y >= self.map_size.h {
+            None
+        } else {
+            Some(self.cursor)
+        };
+        self.cursor.v.x += 1;
+        if self.cursor.v.x >= self.map_size.w {
+            self.cursor.v.x = 0;
+            self.cursor.v.y += 1;
+        }
+        current_pos
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TerrainMap {
+    size: Size2<MInt>,
+    tiles: Vec<TerrainTypeId>,
+}
+
+impl TerrainMap {
+    pub fn new(size: Size2<MInt>, terrain_type_id: TerrainTypeId) -> TerrainMap {
+        TerrainMap {
+            size,
+            tiles: vec![terrain_type_id; (size.w * size.h) as usize],
+        }
+    }
+
+    // Every row is a string of terrain type symbols
+    pub fn from_rows(object_types: &ObjectTypes, size: Size2<MInt>, rows: &[String]) -> TerrainMap {
+        assert_eq!(rows.len() as MInt, size.h);
+        let mut tiles = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            assert_eq!(row.chars().count() as MInt, size.w);
+            for symbol in row.chars() {
+                match object_types.get_terrain_type_id_by_symbol(symbol) {
+                    Some(id) => tiles.push(id),
+                    None => panic!("Terrain map row {}: unknown terrain symbol '{}'", y, symbol),
+                }
+            }
+        }
+        TerrainMap { size, tiles }
+    }
+
+    pub fn get_size(&self) -> Size2<MInt> {
+        self.size
+    }
+
+    pub fn is_inboard(&self, pos: MapPos) -> bool {
+        let x = pos.v.x;
+        let y = pos.v.y;
+        x >= 0 && y >= 0 && x < self.size.w && y < self.size.h
+    }
+
+    pub fn terrain_type_id(&self, pos: MapPos) -> TerrainTypeId {
+        assert!(self.is_inboard(pos));
+        self.tiles[(pos.v.x + pos.v.y * self.size.w) as usize]
+    }
+}
+
+pub fn distance(from: MapPos, to: MapPos) -> MInt {
+    hex::distance(from, to)
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
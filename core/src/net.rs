@@ -0,0 +1,242 @@
+// See LICENSE file for copyright and license details.
+
+// Network game: every message is one line of JSON
+
+use crate::core::{Command, CommandError, Core, Event, ObjectTypes, Player};
+use crate::fs::FileSystem;
+use crate::game::Game;
+use crate::map::TerrainMap;
+use crate::types::PlayerId;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+
+#[derive(Serialize, Deserialize)]
+pub enum ClientMessage {
+    // None takes the first free seat, reconnected player asks for its old one
+    Join(Option<PlayerId>),
+    Command(Command),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameInfo {
+    pub player_id: PlayerId,
+    pub current_player_id: PlayerId,
+    pub players: Vec<Player>,
+    pub map: TerrainMap,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum ServerMessage {
+    // followed by events that recreate player's view
+    Joined(GameInfo),
+    JoinRejected(String),
+    Event(Event),
+    // answer to every command, sent after all events caused by it
+    CommandResult(Result<(), CommandError>),
+}
+
+pub fn send_message<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).unwrap();
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// Ok(None) if connection is closed or broken
+pub fn read_message<T: DeserializeOwned>(reader: &mut dyn BufRead) -> Result<Option<T>, String> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) | Err(_) => Ok(None),
+        Ok(_) => match serde_json::from_str(&line) {
+            Ok(message) => Ok(Some(message)),
+            Err(err) => Err(format!("Bad message: {}", err)),
+        },
+    }
+}
+
+fn spawn_reader(stream: &TcpStream) -> io::Result<Receiver<ServerMessage>> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (messages_tx, messages_rx) = channel();
+    let _ = thread::spawn(move || {
+        // client can not recover from a bad message, so it breaks the connection
+        while let Ok(Some(message)) = read_message(&mut reader) {
+            if messages_tx.send(message).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(messages_rx)
+}
+
+// Client side of a network game: one player's view of the server's Core
+pub struct RemoteGame {
+    stream: TcpStream,
+    messages_rx: Receiver<ServerMessage>,
+    // events that came while waiting for a command result
+    events: VecDeque<Event>,
+    info: GameInfo,
+    object_types: ObjectTypes,
+    is_connected: bool,
+}
+
+impl RemoteGame {
+    // Object types are not sent, client uses its own data files
+    pub fn connect(
+        fs: &FileSystem,
+        address: &str,
+        player_id: Option<PlayerId>,
+    ) -> Result<RemoteGame, String> {
+        let mut stream = match TcpStream::connect(address) {
+            Ok(stream) => stream,
+            Err(err) => return Err(format!("Can not connect to {}: {}", address, err)),
+        };
+        // messages are small and must not wait for each other
+        let _ = stream.set_nodelay(true);
+        let messages_rx = match spawn_reader(&stream) {
+            Ok(messages_rx) => messages_rx,
+            Err(err) => return Err(format!("Can not read from {}: {}", address, err)),
+        };
+        match send_message(&mut stream, &ClientMessage::Join(player_id)) {
+            Ok(()) => {}
+            Err(err) => return Err(format!("Can not join {}: {}", address, err)),
+        }
+        let info = match messages_rx.recv() {
+            Ok(ServerMessage::Joined(info)) => info,
+            Ok(ServerMessage::JoinRejected(reason)) => return Err(reason),
+            Ok(_) => return Err("Unexpected message from server".to_string()),
+            Err(_) => return Err("Server closed connection".to_string()),
+        };
+        let object_types = ObjectTypes::new(&fs.get(Path::new("data/object_types.json")));
+        Ok(RemoteGame {
+            stream,
+            messages_rx,
+            events: VecDeque::new(),
+            info,
+            object_types,
+            is_connected: true,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    // Returns command result, events are queued
+    fn handle_message(&mut self, message: ServerMessage) -> Option<Result<(), CommandError>> {
+        match message {
+            ServerMessage::Event(event) => {
+                self.events.push_back(event);
+                None
+            }
+            ServerMessage::CommandResult(result) => Some(result),
+            // join is answered only once, in connect
+            ServerMessage::Joined(_) | ServerMessage::JoinRejected(_) => None,
+        }
+    }
+
+    fn pop_event(&mut self) -> Option<Event> {
+        let event = self.events.pop_front();
+        if let Some(Event::EventEndTurn(_, ref new_player_id)) = event {
+            self.info.current_player_id = new_player_id.clone();
+        }
+        event
+    }
+
+    // Blocks until the next event, None if disconnected
+    pub fn wait_event(&mut self) -> Option<Event> {
+        while self.events.is_empty() {
+            match self.messages_rx.recv() {
+                Ok(message) => {
+                    let _ = self.handle_message(message);
+                }
+                Err(_) => {
+                    self.is_connected = false;
+                    return None;
+                }
+            }
+        }
+        self.pop_event()
+    }
+}
+
+// Reader thread keeps a clone of the stream,
+// so the connection must be closed explicitly
+impl Drop for RemoteGame {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+impl Game for RemoteGame {
+    fn object_types(&self) -> &ObjectTypes {
+        &self.object_types
+    }
+
+    fn players(&self) -> &Vec<Player> {
+        &self.info.players
+    }
+
+    fn map(&self) -> &TerrainMap {
+        &self.info.map
+    }
+
+    fn player_id(&self) -> PlayerId {
+        self.info.player_id.clone()
+    }
+
+    // Changes when EventEndTurn is taken, not when it is received
+    fn current_player_id(&self) -> PlayerId {
+        self.info.current_player_id.clone()
+    }
+
+    fn get_event(&mut self) -> Option<Event> {
+        loop {
+            match self.messages_rx.try_recv() {
+                Ok(message) => {
+                    let _ = self.handle_message(message);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.is_connected = false;
+                    break;
+                }
+            }
+        }
+        self.pop_event()
+    }
+
+    // Waits for the server's answer
+    fn do_command(&mut self, command: Command) -> Result<(), CommandError> {
+        if !self.is_connected {
+            return Err(CommandError::Disconnected);
+        }
+        if send_message(&mut self.stream, &ClientMessage::Command(command)).is_err() {
+            self.is_connected = false;
+            return Err(CommandError::Disconnected);
+        }
+        loop {
+            match self.messages_rx.recv() {
+                Ok(message) => {
+                    if let Some(result) = self.handle_message(message) {
+                        return result;
+                    }
+                }
+                Err(_) => {
+                    self.is_connected = false;
+                    return Err(CommandError::Disconnected);
+                }
+            }
+        }
+    }
+
+    fn core(&self) -> Option<&Core> {
+        None
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,324 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::{ObjectTypes, Unit};
+use crate::dir::Dir;
+use crate::game_state::GameState;
+use crate::map::distance;
+use crate::types::{MInt, MapPos, Size2};
+use crate::zoc::is_move_stopped_at;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+pub struct Tile {
+    pub cost: MInt,
+    pub parent: Option<Dir>,
+    // unit has to end its move here: tile is in enemy zone of control
+    pub is_stop: bool,
+}
+
+pub struct Map {
+    size: Size2<MInt>,
+    tiles: Vec<Tile>,
+}
+
+fn max_cost() -> MInt {
+    30000
+}
+
+impl<'a> Map {
+    pub fn tile_mut(&'a mut self, pos: MapPos) -> Option<&mut Tile> {
+        self.tiles
+            .get_mut((pos.v.x + pos.v.y * self.size.w) as usize)
+    }
+
+    pub fn tile(&'a self, pos: MapPos) -> &'a Tile {
+        &self.tiles[(pos.v.x + pos.v.y * self.size.w) as usize]
+    }
+
+    pub fn is_inboard(&self, pos: MapPos) -> bool {
+        let x = pos.v.x;
+        let y = pos.v.y;
+        x >= 0 && y >= 0 && x < self.size.w && y < self.size.h
+    }
+
+    pub fn get_size(&self) -> Size2<MInt> {
+        self.size
+    }
+}
+
+pub struct Pathfinder {
+    queue: BinaryHeap<QueueItem>,
+    map: Map,
+}
+
+// Min-heap item: the lower the priority the earlier the position is popped
+struct QueueItem {
+    priority: MInt,
+    // cost of the path to pos when the item was pushed
+    cost: MInt,
+    pos: MapPos,
+}
+
+impl PartialEq for QueueItem {
+    fn eq(&self, other: &QueueItem) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for QueueItem {}
+
+impl PartialOrd for QueueItem {
+    fn partial_cmp(&self, other: &QueueItem) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueItem {
+    fn cmp(&self, other: &QueueItem) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+fn create_tiles(tiles_count: MInt) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for _ in 0..tiles_count {
+        tiles.push(Tile {
+            cost: 0,
+            parent: None,
+            is_stop: false,
+        });
+    }
+    tiles
+}
+
+pub fn get_move_cost(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    unit: &Unit,
+    pos: MapPos,
+) -> Option<MInt> {
+    let unit_class = object_types.get_unit_type(unit.type_id).class;
+    let terrain_type_id = state.map.terrain_type_id(pos);
+    object_types
+        .get_terrain_type(terrain_type_id)
+        .move_cost(unit_class)
+}
+
+// Total cost of moving unit along the path, path[0] is unit's position
+pub fn get_path_cost(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    unit: &Unit,
+    path: &[MapPos],
+) -> MInt {
+    let mut cost = 0;
+    for &pos in path.iter().skip(1) {
+        cost += match get_move_cost(object_types, state, unit, pos) {
+            Some(move_cost) => move_cost,
+            None => panic!("Impassable tile in path: {:?}", pos),
+        };
+    }
+    cost
+}
+
+// Cheapest possible step for this unit, used as A* heuristic multiplier
+fn get_min_move_cost(object_types: &ObjectTypes, unit: &Unit) -> MInt {
+    let unit_class = object_types.get_unit_type(unit.type_id).class;
+    let mut min_cost = max_cost();
+    for terrain_type in object_types.terrain_types().iter() {
+        if let Some(cost) = terrain_type.move_cost(unit_class) {
+            min_cost = std::cmp::min(min_cost, cost);
+        }
+    }
+    min_cost
+}
+
+impl Pathfinder {
+    pub fn new(map_size: Size2<MInt>) -> Pathfinder {
+        let tiles_count = map_size.w * map_size.h;
+        Pathfinder {
+            queue: BinaryHeap::new(),
+            map: Map {
+                size: map_size,
+                tiles: create_tiles(tiles_count),
+            },
+        }
+    }
+
+    pub fn get_map(&self) -> &Map {
+        &self.map
+    }
+
+    // Returns true if neighbour's cost was improved
+    fn process_neighbour_pos(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        unit: &Unit,
+        original_pos: MapPos,
+        neighbour_pos: MapPos,
+        max_cost: MInt,
+    ) -> bool {
+        let move_cost = match get_move_cost(object_types, state, unit, neighbour_pos) {
+            Some(move_cost) => move_cost,
+            None => return false,
+        };
+        let old_cost = self.map.tile(original_pos).cost;
+        let tile = self.map.tile_mut(neighbour_pos).unwrap();
+        let new_cost = old_cost + move_cost;
+        let units_count = state.units_at(neighbour_pos).len();
+        if tile.cost > new_cost && units_count == 0 && new_cost <= max_cost {
+            tile.cost = new_cost;
+            tile.parent = Some(Dir::get_dir_from_to(neighbour_pos, original_pos));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn clean_map(&mut self) {
+        for tile in self.map.tiles.iter_mut() {
+            tile.cost = max_cost();
+            tile.parent = None;
+            tile.is_stop = false;
+        }
+    }
+
+    // Paths do not go on from tiles where the unit has to stop,
+    // except the one it starts from
+    fn is_stop(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        unit: &Unit,
+        pos: MapPos,
+    ) -> bool {
+        if pos == unit.pos || !is_move_stopped_at(object_types, state, unit, pos) {
+            return false;
+        }
+        self.map.tile_mut(pos).unwrap().is_stop = true;
+        true
+    }
+
+    fn push_start_pos_to_queue(&mut self, start_pos: MapPos) {
+        self.queue.push(QueueItem {
+            priority: 0,
+            cost: 0,
+            pos: start_pos,
+        });
+        let start_tile = self.map.tile_mut(start_pos).unwrap();
+        start_tile.cost = 0;
+        start_tile.parent = None;
+    }
+
+    // Dijkstra: every tile that unit can reach with its move points
+    pub fn fill_map(&mut self, object_types: &ObjectTypes, state: &GameState, unit: &Unit) {
+        self.clean_map();
+        self.queue.clear();
+        self.push_start_pos_to_queue(unit.pos);
+        while let Some(QueueItem { cost, pos, .. }) = self.queue.pop() {
+            if cost > self.map.tile(pos).cost {
+                // outdated item, this tile was already reached cheaper
+                continue;
+            }
+            if self.is_stop(object_types, state, unit, pos) {
+                continue;
+            }
+            for i in 0..6 {
+                let neighbour_pos = Dir::get_neighbour_pos(pos, Dir::from_int(i as MInt));
+                if !self.map.is_inboard(neighbour_pos) {
+                    continue;
+                }
+                if self.process_neighbour_pos(
+                    object_types,
+                    state,
+                    unit,
+                    pos,
+                    neighbour_pos,
+                    unit.move_points,
+                ) {
+                    let cost = self.map.tile(neighbour_pos).cost;
+                    self.queue.push(QueueItem {
+                        priority: cost,
+                        cost,
+                        pos: neighbour_pos,
+                    });
+                }
+            }
+        }
+    }
+
+    // A*: shortest path to one tile, not limited by unit's move points.
+    // Overwrites the map filled by fill_map.
+    pub fn find_path(
+        &mut self,
+        object_types: &ObjectTypes,
+        state: &GameState,
+        unit: &Unit,
+        destination: MapPos,
+    ) -> Option<Vec<MapPos>> {
+        if !self.map.is_inboard(destination) || !state.units_at(destination).is_empty() {
+            return None;
+        }
+        let min_move_cost = get_min_move_cost(object_types, unit);
+        self.clean_map();
+        self.queue.clear();
+        self.push_start_pos_to_queue(unit.pos);
+        while let Some(QueueItem { cost, pos, .. }) = self.queue.pop() {
+            if cost > self.map.tile(pos).cost {
+                // outdated item, this tile was already reached cheaper
+                continue;
+            }
+            if pos == destination {
+                self.queue.clear();
+                return self.get_path(destination);
+            }
+            if self.is_stop(object_types, state, unit, pos) {
+                continue;
+            }
+            for i in 0..6 {
+                let neighbour_pos = Dir::get_neighbour_pos(pos, Dir::from_int(i as MInt));
+                if !self.map.is_inboard(neighbour_pos) {
+                    continue;
+                }
+                if self.process_neighbour_pos(
+                    object_types,
+                    state,
+                    unit,
+                    pos,
+                    neighbour_pos,
+                    max_cost(),
+                ) {
+                    let cost = self.map.tile(neighbour_pos).cost;
+                    let heuristic = distance(neighbour_pos, destination) * min_move_cost;
+                    self.queue.push(QueueItem {
+                        priority: cost + heuristic,
+                        cost,
+                        pos: neighbour_pos,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    // None if destination was not reached by fill_map or find_path
+    pub fn get_path(&self, destination: MapPos) -> Option<Vec<MapPos>> {
+        if !self.map.is_inboard(destination) || self.map.tile(destination).cost == max_cost() {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut pos = destination;
+        path.push(destination);
+        while self.map.tile(pos).cost != 0 {
+            let parent_dir = self.map.tile(pos).parent.unwrap();
+            pos = Dir::get_neighbour_pos(pos, parent_dir);
+            assert!(self.map.is_inboard(pos));
+            path.push(pos);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
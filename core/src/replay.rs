@@ -0,0 +1,72 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::{Command, Event, Player};
+use crate::map::TerrainMap;
+use crate::misc::{read_file, write_file};
+use crate::types::MInt;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const REPLAY_VERSION: MInt = 6;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ReplayEntry {
+    ReplayCommand(Command),
+    // core events, not filtered by fog of war
+    ReplayEvent(Event),
+}
+
+// Everything that happened in a game, in order
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: MInt,
+    pub seed: u64,
+    pub map: TerrainMap,
+    pub players: Vec<Player>,
+    pub log: Vec<ReplayEntry>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, map: TerrainMap, players: Vec<Player>) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            seed,
+            map,
+            players,
+            log: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Replay {
+        let replay: Replay = match serde_json::from_str(&read_file(path)) {
+            Ok(replay) => replay,
+            Err(msg) => panic!("Can not parse replay {}: {}", path.display(), msg),
+        };
+        if replay.version != REPLAY_VERSION {
+            panic!(
+                "Replay {}: unsupported version {}, expected {}",
+                path.display(),
+                replay.version,
+                REPLAY_VERSION
+            );
+        }
+        replay
+    }
+
+    pub fn save(&self, path: &Path) {
+        write_file(path, &serde_json::to_string(self).unwrap());
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+        for entry in self.log.iter() {
+            match *entry {
+                ReplayEntry::ReplayEvent(ref event) => events.push(event.clone()),
+                ReplayEntry::ReplayCommand(_) => {}
+            }
+        }
+        events
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
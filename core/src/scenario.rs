@@ -0,0 +1,126 @@
+// See LICENSE file for copyright and license details.
+
+use crate::misc::read_file;
+use crate::types::{MInt, MapPos, Size2};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioPlayer {
+    pub name: String,
+    // controlled by computer, false if missing
+    pub ai: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioUnit {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    // index in players list
+    pub player: MInt,
+    pub pos: MapPos,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub map_size: Size2<MInt>,
+    // rows of terrain type symbols, the first terrain type is used if missing
+    pub terrain: Option<Vec<String>>,
+    pub players: Vec<ScenarioPlayer>,
+    pub units: Vec<ScenarioUnit>,
+    pub max_turns: Option<MInt>,
+    // combat RNG seed, random if missing
+    pub seed: Option<u64>,
+    // hexes that win the game if held long enough
+    pub objectives: Option<Vec<MapPos>>,
+    pub objective_hold_turns: Option<MInt>,
+}
+
+impl Scenario {
+    pub fn new(path: &Path) -> Scenario {
+        let scenario: Scenario = match serde_json::from_str(&read_file(path)) {
+            Ok(scenario) => scenario,
+            Err(msg) => panic!("Can not parse scenario {}: {}", path.display(), msg),
+        };
+        scenario.check(path);
+        scenario
+    }
+
+    fn is_inboard(&self, pos: MapPos) -> bool {
+        let x = pos.v.x;
+        let y = pos.v.y;
+        x >= 0 && y >= 0 && x < self.map_size.w && y < self.map_size.h
+    }
+
+    fn check(&self, path: &Path) {
+        let name = path.display();
+        if self.map_size.w <= 0 || self.map_size.h <= 0 {
+            panic!("{}: bad map size", name);
+        }
+        if let Some(ref rows) = self.terrain {
+            if rows.len() as MInt != self.map_size.h {
+                panic!(
+                    "{}: terrain has {} rows instead of {}",
+                    name,
+                    rows.len(),
+                    self.map_size.h
+                );
+            }
+            for (y, row) in rows.iter().enumerate() {
+                if row.chars().count() as MInt != self.map_size.w {
+                    panic!(
+                        "{}: terrain row {} is not {} tiles long",
+                        name, y, self.map_size.w
+                    );
+                }
+            }
+        }
+        if self.players.is_empty() {
+            panic!("{}: no players", name);
+        }
+        if let Some(max_turns) = self.max_turns {
+            if max_turns <= 0 {
+                panic!("{}: bad max_turns: {}", name, max_turns);
+            }
+        }
+        if let Some(hold_turns) = self.objective_hold_turns {
+            if hold_turns <= 0 {
+                panic!("{}: bad objective_hold_turns: {}", name, hold_turns);
+            }
+        }
+        if let Some(ref objectives) = self.objectives {
+            for (i, &pos) in objectives.iter().enumerate() {
+                if !self.is_inboard(pos) {
+                    panic!(
+                        "{}: objective {}: position {:?} is out of map",
+                        name, i, pos.v
+                    );
+                }
+            }
+        }
+        for (i, unit) in self.units.iter().enumerate() {
+            if unit.player < 0 || unit.player >= self.players.len() as MInt {
+                panic!("{}: unit {}: no player with index {}", name, i, unit.player);
+            }
+            if !self.is_inboard(unit.pos) {
+                panic!(
+                    "{}: unit {}: position {:?} is out of map",
+                    name, i, unit.pos.v
+                );
+            }
+            for other in self.units[..i].iter() {
+                if other.pos == unit.pos {
+                    panic!(
+                        "{}: unit {}: position {:?} is occupied",
+                        name, i, unit.pos.v
+                    );
+                }
+            }
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,303 @@
+// See LICENSE file for copyright and license details.
+
+use crate::ai::Ai;
+use crate::core::Command::CommandEndTurn;
+use crate::core::Event::EventGameOver;
+use crate::core::{get_view_events, Command, CommandError, Core};
+use crate::net::{read_message, send_message, ClientMessage, GameInfo, ServerMessage};
+use crate::types::{MInt, PlayerId};
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct ConnectionId {
+    pub id: MInt,
+}
+
+enum ConnectionEvent {
+    Connected(TcpStream),
+    Message(ClientMessage),
+    // is followed by Disconnected
+    BadMessage(String),
+    // connection with this id could not be accepted
+    AcceptFailed(String),
+    Disconnected,
+}
+
+type ConnectionEventSender = Sender<(ConnectionId, ConnectionEvent)>;
+
+struct Connection {
+    stream: TcpStream,
+    // None until the client joins
+    player_id: Option<PlayerId>,
+}
+
+fn spawn_connection_reader(id: ConnectionId, stream: TcpStream, events_tx: ConnectionEventSender) {
+    let _ = thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let event = match read_message(&mut reader) {
+                Ok(Some(message)) => ConnectionEvent::Message(message),
+                Ok(None) => break,
+                Err(err) => {
+                    let _ = events_tx.send((id, ConnectionEvent::BadMessage(err)));
+                    break;
+                }
+            };
+            if events_tx.send((id, event)).is_err() {
+                return;
+            }
+        }
+        let _ = events_tx.send((id, ConnectionEvent::Disconnected));
+    });
+}
+
+fn spawn_listener(listener: TcpListener, events_tx: ConnectionEventSender) {
+    let _ = thread::spawn(move || {
+        let mut next_id = 0;
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let event = ConnectionEvent::AcceptFailed(err.to_string());
+                    if events_tx
+                        .send((ConnectionId { id: next_id }, event))
+                        .is_err()
+                    {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let _ = stream.set_nodelay(true);
+            let writer = match stream.try_clone() {
+                Ok(writer) => writer,
+                Err(_) => continue,
+            };
+            let id = ConnectionId { id: next_id };
+            next_id += 1;
+            // Connected always comes before messages of the connection
+            if events_tx
+                .send((id, ConnectionEvent::Connected(writer)))
+                .is_err()
+            {
+                return;
+            }
+            spawn_connection_reader(id, stream, events_tx.clone());
+        }
+    });
+}
+
+fn get_ais(core: &Core) -> HashMap<PlayerId, Ai> {
+    let mut m = HashMap::new();
+    for player in core.players().iter() {
+        if player.is_ai {
+            let _ = m.insert(
+                player.id.clone(),
+                Ai::new(player.id.clone(), core.map_size()),
+            );
+        }
+    }
+    m
+}
+
+// Owns Core and plays it with clients: every client is one player
+// and receives only events of that player. Disconnected player keeps
+// its seat, the game waits until it joins again. Seat of a connected
+// player can not be taken.
+pub struct Server {
+    core: Core,
+    ais: HashMap<PlayerId, Ai>,
+    connections: HashMap<ConnectionId, Connection>,
+    events_rx: Receiver<(ConnectionId, ConnectionEvent)>,
+}
+
+impl Server {
+    pub fn new(core: Core, listener: TcpListener) -> Server {
+        let (events_tx, events_rx) = channel();
+        spawn_listener(listener, events_tx);
+        Server {
+            ais: get_ais(&core),
+            core,
+            connections: HashMap::new(),
+            events_rx,
+        }
+    }
+
+    // Returns when the game is over and all clients are gone.
+    // Joins, disconnects and connection errors are passed to log.
+    pub fn run(&mut self, log: &mut dyn FnMut(&str)) {
+        self.play_ais();
+        while let Ok((id, event)) = self.events_rx.recv() {
+            match event {
+                ConnectionEvent::Connected(stream) => {
+                    let connection = Connection {
+                        stream,
+                        player_id: None,
+                    };
+                    let _ = self.connections.insert(id, connection);
+                }
+                ConnectionEvent::Message(ClientMessage::Join(player_id)) => {
+                    if let Some(player_id) = self.join(id, player_id) {
+                        log(&format!("{} joined", self.get_player_name(&player_id)));
+                    }
+                }
+                ConnectionEvent::Message(ClientMessage::Command(command)) => {
+                    self.handle_command(id, command);
+                }
+                ConnectionEvent::Disconnected => {
+                    let connection = self.connections.remove(&id);
+                    if let Some(Connection {
+                        player_id: Some(player_id),
+                        ..
+                    }) = connection
+                    {
+                        log(&format!(
+                            "{} disconnected",
+                            self.get_player_name(&player_id)
+                        ));
+                    }
+                }
+                ConnectionEvent::BadMessage(err) => log(&err),
+                ConnectionEvent::AcceptFailed(err) => {
+                    log(&format!("Can not accept connection: {}", err));
+                }
+            }
+            if self.core.game_result().is_some() && self.connections.is_empty() {
+                return;
+            }
+        }
+    }
+
+    fn get_player_name(&self, player_id: &PlayerId) -> &str {
+        &self.core.players()[player_id.id as usize].name
+    }
+
+    fn is_seat_taken(&self, player_id: &PlayerId) -> bool {
+        self.connections
+            .values()
+            .any(|connection| connection.player_id.as_ref() == Some(player_id))
+    }
+
+    fn get_seat(&self, player_id: Option<PlayerId>) -> Result<PlayerId, String> {
+        let players = self.core.players();
+        let player_id = match player_id {
+            Some(player_id) => player_id,
+            None => match players
+                .iter()
+                .find(|player| !player.is_ai && !self.is_seat_taken(&player.id))
+            {
+                Some(player) => player.id.clone(),
+                None => return Err("No free seats".to_string()),
+            },
+        };
+        if player_id.id < 0 || player_id.id >= players.len() as MInt {
+            return Err(format!("No player {}", player_id.id));
+        }
+        if players[player_id.id as usize].is_ai {
+            return Err(format!("Player {} is computer", player_id.id));
+        }
+        if self.is_seat_taken(&player_id) {
+            return Err(format!("Player {} is already connected", player_id.id));
+        }
+        Ok(player_id)
+    }
+
+    fn send(&mut self, id: ConnectionId, message: &ServerMessage) {
+        // broken connections are removed when their reader stops
+        if let Some(connection) = self.connections.get_mut(&id) {
+            let _ = send_message(&mut connection.stream, message);
+        }
+    }
+
+    // New client knows nothing, so it gets the whole view of the player.
+    // Returns the taken seat.
+    fn join(&mut self, id: ConnectionId, player_id: Option<PlayerId>) -> Option<PlayerId> {
+        // connection was dropped after the message was queued
+        let connection = self.connections.get(&id)?;
+        if connection.player_id.is_some() {
+            let reason = "Already joined".to_string();
+            self.send(id, &ServerMessage::JoinRejected(reason));
+            return None;
+        }
+        let player_id = match self.get_seat(player_id) {
+            Ok(player_id) => player_id,
+            Err(reason) => {
+                self.send(id, &ServerMessage::JoinRejected(reason));
+                return None;
+            }
+        };
+        self.connections.get_mut(&id).unwrap().player_id = Some(player_id.clone());
+        while self.core.get_player_event(&player_id).is_some() {}
+        let info = GameInfo {
+            player_id: player_id.clone(),
+            current_player_id: self.core.player_id(),
+            players: self.core.players().clone(),
+            map: self.core.map().clone(),
+        };
+        self.send(id, &ServerMessage::Joined(info));
+        let mut events = get_view_events(self.core.view(&player_id));
+        if let Some(result) = self.core.game_result() {
+            events.push(EventGameOver(result.clone()));
+        }
+        for event in events.into_iter() {
+            self.send(id, &ServerMessage::Event(event));
+        }
+        Some(player_id)
+    }
+
+    fn handle_command(&mut self, id: ConnectionId, command: Command) {
+        let connection = match self.connections.get(&id) {
+            Some(connection) => connection,
+            None => return,
+        };
+        let result = match connection.player_id {
+            Some(ref player_id) if *player_id == self.core.player_id() => {
+                self.core.do_command(command)
+            }
+            _ => Err(CommandError::NotYourTurn),
+        };
+        self.play_ais();
+        self.send_events();
+        self.send(id, &ServerMessage::CommandResult(result));
+    }
+
+    fn send_events(&mut self) {
+        let ids: Vec<ConnectionId> = self.connections.keys().cloned().collect();
+        for id in ids.into_iter() {
+            let player_id = match self.connections[&id].player_id {
+                Some(ref player_id) => player_id.clone(),
+                None => continue,
+            };
+            while let Some(event) = self.core.get_player_event(&player_id) {
+                self.send(id, &ServerMessage::Event(event));
+            }
+        }
+    }
+
+    fn play_ais(&mut self) {
+        while self.core.game_result().is_none() {
+            let player_id = self.core.player_id();
+            let ai = match self.ais.get_mut(&player_id) {
+                Some(ai) => ai,
+                None => return,
+            };
+            // AI looks at its view, so it must take its events first
+            while self.core.get_player_event(&player_id).is_some() {}
+            let command = ai.get_command(self.core.object_types(), self.core.view(&player_id));
+            let is_end_turn = match command {
+                CommandEndTurn => true,
+                // AI sees less than core, so give up the turn instead of looping
+                command => self.core.do_command(command).is_err(),
+            };
+            if is_end_turn {
+                let _ = self.core.do_command(CommandEndTurn);
+            }
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,47 @@
+// See LICENSE file for copyright and license details.
+
+use cgmath::Vector2;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Size2<T> {
+    pub w: T,
+    pub h: T,
+}
+
+pub type MInt = i32;
+
+#[derive(PartialOrd, PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerId {
+    pub id: MInt,
+}
+
+#[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UnitId {
+    pub id: MInt,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct MapPos {
+    pub v: Vector2<MInt>,
+}
+
+// Written as [x, y] in data files
+impl Serialize for MapPos {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.v.x, self.v.y).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MapPos {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<MapPos, D::Error> {
+        let (x, y) = <(MInt, MInt)>::deserialize(deserializer)?;
+        Ok(MapPos {
+            v: Vector2 { x, y },
+        })
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,209 @@
+// See LICENSE file for copyright and license details.
+
+use crate::core::Event::{EventAttackUnit, EventEndTurn};
+use crate::core::{Event, Player};
+use crate::game_state::GameState;
+use crate::scenario::Scenario;
+use crate::types::{MInt, MapPos, PlayerId, UnitId};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_OBJECTIVE_HOLD_TURNS: MInt = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum VictoryReason {
+    EnemiesDestroyed,
+    ObjectivesHeld,
+    TurnLimit,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub player_id: PlayerId,
+    // one point for every killed unit and every turn an objective is held
+    pub victory_points: MInt,
+    pub units_killed: MInt,
+    pub units_lost: MInt,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameResult {
+    // None is a draw
+    pub winner: Option<PlayerId>,
+    pub reason: VictoryReason,
+    pub turns: MInt,
+    pub stats: Vec<PlayerStats>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Objective {
+    pos: MapPos,
+    holder: Option<PlayerId>,
+    held_turns: MInt,
+}
+
+// Watches core events and decides when the game is over
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Victory {
+    objectives: Vec<Objective>,
+    objective_hold_turns: MInt,
+    max_turns: Option<MInt>,
+    turn: MInt,
+    stats: Vec<PlayerStats>,
+}
+
+fn get_objectives(scenario: &Scenario) -> Vec<Objective> {
+    let mut objectives = Vec::new();
+    if let Some(ref positions) = scenario.objectives {
+        for &pos in positions.iter() {
+            objectives.push(Objective {
+                pos,
+                holder: None,
+                held_turns: 0,
+            });
+        }
+    }
+    objectives
+}
+
+fn get_stats(players: &[Player]) -> Vec<PlayerStats> {
+    let mut stats = Vec::new();
+    for player in players.iter() {
+        stats.push(PlayerStats {
+            player_id: player.id.clone(),
+            victory_points: 0,
+            units_killed: 0,
+            units_lost: 0,
+        });
+    }
+    stats
+}
+
+impl Victory {
+    pub fn new(scenario: &Scenario, players: &[Player]) -> Victory {
+        Victory {
+            objectives: get_objectives(scenario),
+            objective_hold_turns: scenario
+                .objective_hold_turns
+                .unwrap_or(DEFAULT_OBJECTIVE_HOLD_TURNS),
+            max_turns: scenario.max_turns,
+            turn: 1,
+            stats: get_stats(players),
+        }
+    }
+
+    pub fn max_turns(&self) -> Option<MInt> {
+        self.max_turns
+    }
+
+    fn stats_mut(&mut self, player_id: &PlayerId) -> &mut PlayerStats {
+        &mut self.stats[player_id.id as usize]
+    }
+
+    fn result(&self, winner: Option<PlayerId>, reason: VictoryReason) -> GameResult {
+        GameResult {
+            winner,
+            reason,
+            turns: self.turn,
+            stats: self.stats.clone(),
+        }
+    }
+
+    // Player with most victory points, None if there is a tie
+    fn get_points_leader(&self) -> Option<PlayerId> {
+        let max_points = self.stats.iter().map(|s| s.victory_points).max().unwrap();
+        let mut leaders = self.stats.iter().filter(|s| s.victory_points == max_points);
+        let leader = leaders.next().unwrap();
+        match leaders.next() {
+            Some(_) => None,
+            None => Some(leader.player_id.clone()),
+        }
+    }
+
+    fn on_kill(
+        &mut self,
+        state: &GameState,
+        attacker_id: UnitId,
+        defender_id: UnitId,
+    ) -> Option<GameResult> {
+        let attacker_player_id = state.units[&attacker_id].player_id.clone();
+        let defender_player_id = state.units[&defender_id].player_id.clone();
+        self.stats_mut(&attacker_player_id).units_killed += 1;
+        self.stats_mut(&attacker_player_id).victory_points += 1;
+        self.stats_mut(&defender_player_id).units_lost += 1;
+        let mut alive_player_ids: Vec<&PlayerId> = Vec::new();
+        for (&id, unit) in state.units.iter() {
+            if id != defender_id && !alive_player_ids.contains(&&unit.player_id) {
+                alive_player_ids.push(&unit.player_id);
+            }
+        }
+        if alive_player_ids.len() == 1 {
+            let winner = alive_player_ids[0].clone();
+            Some(self.result(Some(winner), VictoryReason::EnemiesDestroyed))
+        } else {
+            None
+        }
+    }
+
+    fn on_end_turn(
+        &mut self,
+        state: &GameState,
+        old_player_id: &PlayerId,
+        new_player_id: &PlayerId,
+    ) -> Option<GameResult> {
+        let mut winner = None;
+        let mut points = 0;
+        for objective in self.objectives.iter_mut() {
+            let is_held = state
+                .units_at(objective.pos)
+                .iter()
+                .any(|unit| unit.player_id == *old_player_id);
+            if is_held {
+                if objective.holder.as_ref() == Some(old_player_id) {
+                    objective.held_turns += 1;
+                } else {
+                    objective.holder = Some(old_player_id.clone());
+                    objective.held_turns = 1;
+                }
+                points += 1;
+                if objective.held_turns >= self.objective_hold_turns {
+                    winner = Some(old_player_id.clone());
+                }
+            } else if objective.holder.as_ref() == Some(old_player_id) {
+                objective.holder = None;
+                objective.held_turns = 0;
+            }
+        }
+        self.stats_mut(old_player_id).victory_points += points;
+        if winner.is_some() {
+            return Some(self.result(winner, VictoryReason::ObjectivesHeld));
+        }
+        // all players have made their moves
+        if new_player_id.id <= old_player_id.id {
+            match self.max_turns {
+                Some(max_turns) if self.turn >= max_turns => {
+                    let leader = self.get_points_leader();
+                    return Some(self.result(leader, VictoryReason::TurnLimit));
+                }
+                _ => self.turn += 1,
+            }
+        }
+        None
+    }
+
+    // Must be called before the event is applied to the state
+    pub fn process_event(&mut self, state: &GameState, event: &Event) -> Option<GameResult> {
+        match *event {
+            EventAttackUnit(attacker_id, defender_id, ref result)
+                if result.is_destroyed(&state.units[&defender_id]) =>
+            {
+                self.on_kill(state, attacker_id, defender_id)
+            }
+            EventEndTurn(ref old_player_id, ref new_player_id) => {
+                self.on_end_turn(state, old_player_id, new_player_id)
+            }
+            _ => None,
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
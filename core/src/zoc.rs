@@ -0,0 +1,44 @@
+// See LICENSE file for copyright and license details.
+
+// Zone of control: tiles next to enemy units. Units of classes listed
+// in object types must end their move when they enter such a tile,
+// but can leave it with the next move. Only enemies that the unit's
+// player sees count, so players' views and the core agree.
+
+use crate::core::{ObjectTypes, Unit};
+use crate::fow::is_visible;
+use crate::game_state::GameState;
+use crate::hex;
+use crate::types::MapPos;
+
+pub fn is_in_enemy_zone_of_control(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    unit: &Unit,
+    pos: MapPos,
+) -> bool {
+    for neighbour_pos in hex::neighbours(pos).into_iter() {
+        for other in state.units_at(neighbour_pos).into_iter() {
+            if other.player_id != unit.player_id
+                && is_visible(object_types, state, &unit.player_id, other.pos)
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// Unit can not go on after entering this tile
+pub fn is_move_stopped_at(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    unit: &Unit,
+    pos: MapPos,
+) -> bool {
+    let unit_class = object_types.get_unit_type(unit.type_id).class;
+    object_types.is_stopped_by_zone_of_control(unit_class)
+        && is_in_enemy_zone_of_control(object_types, state, unit, pos)
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,63 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_events, new_core};
+use marauder_core::ai::Ai;
+use marauder_core::core::Command::{CommandAttackUnit, CommandEndTurn};
+use marauder_core::core::{Command, Core};
+use marauder_core::game_state::GameState;
+use marauder_core::types::{PlayerId, UnitId};
+
+const BLUE: PlayerId = PlayerId { id: 0 };
+
+// AI sees the game only through events of its player
+fn get_ai_command(core: &mut Core, ai: &mut Ai, state: &mut GameState) -> Command {
+    for event in get_events(core).iter() {
+        state.apply_event(core.object_types(), event);
+    }
+    ai.get_command(core.object_types(), state)
+}
+
+#[test]
+fn ai_attacks_enemy_in_sight() {
+    let mut core = new_core("duel.json");
+    let mut ai = Ai::new(BLUE, core.map_size());
+    let mut state = GameState::new(core.map().clone());
+    // only the cannon reaches the soldier
+    match get_ai_command(&mut core, &mut ai, &mut state) {
+        CommandAttackUnit(attacker_id, defender_id, weapon_index) => {
+            assert_eq!(attacker_id, UnitId { id: 0 });
+            assert_eq!(defender_id, UnitId { id: 1 });
+            assert_eq!(weapon_index, Some(0));
+        }
+        _ => panic!("Expected CommandAttackUnit"),
+    }
+}
+
+#[test]
+fn ai_commands_are_accepted_and_end_turns() {
+    let mut core = new_core("duel.json");
+    let mut ai = Ai::new(BLUE, core.map_size());
+    let mut state = GameState::new(core.map().clone());
+    for _ in 0..5 {
+        let mut commands_count = 0;
+        loop {
+            let command = get_ai_command(&mut core, &mut ai, &mut state);
+            let is_end_turn = matches!(command, CommandEndTurn);
+            core.do_command(command).unwrap();
+            if is_end_turn || core.game_result().is_some() {
+                break;
+            }
+            commands_count += 1;
+            assert!(commands_count < 20);
+        }
+        if core.game_result().is_some() {
+            return;
+        }
+        // the other player only ends its turns
+        core.do_command(CommandEndTurn).unwrap();
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
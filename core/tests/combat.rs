@@ -0,0 +1,137 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_fs, pos};
+use marauder_core::combat::{attack, get_test_values, is_passed, predict, roll, AttackOutcome};
+use marauder_core::core::{ObjectTypes, Unit, UnitTypeId};
+use marauder_core::types::{MInt, PlayerId, UnitId};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::path::Path;
+
+fn get_object_types() -> ObjectTypes {
+    ObjectTypes::new(&get_fs().get(Path::new("data/object_types.json")))
+}
+
+fn unit(id: MInt, type_id: MInt, count: MInt) -> Unit {
+    Unit {
+        id: UnitId { id },
+        pos: pos(0, id),
+        player_id: PlayerId { id },
+        type_id: UnitTypeId { id: type_id },
+        move_points: 0,
+        attacked: false,
+        overwatch: false,
+        count,
+    }
+}
+
+#[test]
+fn prediction_matches_rolls() {
+    let object_types = get_object_types();
+    let mut rng = Pcg32::seed_from_u64(common::SEED);
+    let attempts = 100_000;
+    // attacker type, weapon index and defender type
+    let cases = [
+        (0, 0, 0),
+        (0, 1, 0),
+        (0, 0, 1),
+        (0, 1, 1),
+        (1, 0, 0),
+        (1, 0, 1),
+    ];
+    for &(attacker_type_id, weapon_index, defender_type_id) in cases.iter() {
+        let attacker = unit(0, attacker_type_id, 1);
+        let defender = unit(1, defender_type_id, 1);
+        let (hit_test_v, pierce_test_v, wound_test_v) =
+            get_test_values(&object_types, &attacker, &defender, weapon_index);
+        let mut kills = 0;
+        for _ in 0..attempts {
+            if is_passed(roll(&mut rng), hit_test_v)
+                && is_passed(roll(&mut rng), pierce_test_v)
+                && is_passed(roll(&mut rng), wound_test_v)
+            {
+                kills += 1;
+            }
+        }
+        let prediction = predict(&object_types, &attacker, &defender, weapon_index);
+        // rounding down of the prediction and randomness
+        let kill_chance = kills * 100 / attempts;
+        assert!((kill_chance - prediction.kill).abs() <= 1);
+    }
+}
+
+#[test]
+fn prediction_counts_all_shots() {
+    let object_types = get_object_types();
+    let mut rng = Pcg32::seed_from_u64(common::SEED);
+    let attempts = 100_000;
+    // four soldiers fire at a squad of two
+    let attacker = unit(0, 1, 4);
+    let defender = unit(1, 1, 2);
+    let mut any_kills = 0;
+    let mut destroys = 0;
+    for _ in 0..attempts {
+        let result = attack(&mut rng, &object_types, &attacker, &defender, 0);
+        if result.killed_count() > 0 {
+            any_kills += 1;
+        }
+        if result.is_destroyed(&defender) {
+            destroys += 1;
+        }
+    }
+    let prediction = predict(&object_types, &attacker, &defender, 0);
+    assert_eq!(prediction.shots, 4);
+    assert!(prediction.any_kill > prediction.kill);
+    assert!(prediction.destroy < prediction.any_kill);
+    assert!((any_kills * 100 / attempts - prediction.any_kill).abs() <= 1);
+    assert!((destroys * 100 / attempts - prediction.destroy).abs() <= 1);
+}
+
+#[test]
+fn shot_result_stops_at_failed_test() {
+    let object_types = get_object_types();
+    let mut rng = Pcg32::seed_from_u64(common::SEED);
+    let attacker = unit(0, 0, 1);
+    let defender = unit(1, 0, 1);
+    let outcomes = [
+        AttackOutcome::Miss,
+        AttackOutcome::NoPierce,
+        AttackOutcome::NoWound,
+        AttackOutcome::Killed,
+    ];
+    for _ in 0..100 {
+        let result = attack(&mut rng, &object_types, &attacker, &defender, 0);
+        assert_eq!(result.shots.len(), 1);
+        let shot = &result.shots[0];
+        let passed_count = shot.rolls.iter().filter(|r| r.is_passed()).count();
+        assert_eq!(shot.outcome, outcomes[passed_count]);
+        assert!(shot.rolls[..passed_count].iter().all(|r| r.is_passed()));
+        assert_eq!(shot.rolls.len(), std::cmp::min(passed_count + 1, 3));
+    }
+}
+
+#[test]
+fn every_model_shoots_till_defender_is_destroyed() {
+    let object_types = get_object_types();
+    let mut rng = Pcg32::seed_from_u64(common::SEED);
+    let attacker = unit(0, 0, 4);
+    let defender = unit(1, 1, 2);
+    for _ in 0..100 {
+        let result = attack(&mut rng, &object_types, &attacker, &defender, 0);
+        let killed_count = result.killed_count();
+        assert!(killed_count <= defender.count);
+        assert_eq!(
+            result.is_destroyed(&defender),
+            killed_count == defender.count
+        );
+        if result.is_destroyed(&defender) {
+            assert!(result.shots.last().unwrap().is_killed());
+        } else {
+            assert_eq!(result.shots.len() as MInt, attacker.count);
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
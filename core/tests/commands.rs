@@ -0,0 +1,233 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_events, new_core, pos, to_json};
+use marauder_core::core::Command::{
+    CommandAttackUnit, CommandCreateUnit, CommandEndTurn, CommandMove,
+};
+use marauder_core::core::CommandError;
+use marauder_core::core::Event::EventAttackUnit;
+use marauder_core::types::{PlayerId, UnitId};
+
+const TANK_ID: UnitId = UnitId { id: 0 };
+const SOLDIER_ID: UnitId = UnitId { id: 1 };
+
+#[test]
+fn bad_commands_are_rejected() {
+    let mut core = new_core("duel.json");
+    let move_soldier = CommandMove(SOLDIER_ID, vec![pos(4, 0), pos(5, 0)]);
+    assert_eq!(core.do_command(move_soldier), Err(CommandError::NotOwnUnit));
+    let move_nobody = CommandMove(UnitId { id: 9 }, vec![pos(0, 1), pos(1, 1)]);
+    assert_eq!(core.do_command(move_nobody), Err(CommandError::NoSuchUnit));
+    let jump = CommandMove(TANK_ID, vec![pos(0, 1), pos(2, 1)]);
+    assert_eq!(core.do_command(jump), Err(CommandError::BadPath));
+    let wrong_start = CommandMove(TANK_ID, vec![pos(1, 1), pos(2, 1)]);
+    assert_eq!(core.do_command(wrong_start), Err(CommandError::BadPath));
+    let out_of_map = CommandMove(TANK_ID, vec![pos(0, 1), pos(-1, 1)]);
+    assert_eq!(core.do_command(out_of_map), Err(CommandError::BadPath));
+    assert_eq!(
+        core.do_command(CommandCreateUnit(pos(0, 1))),
+        Err(CommandError::TileIsOccupied)
+    );
+    assert_eq!(
+        core.do_command(CommandCreateUnit(pos(-1, 0))),
+        Err(CommandError::OutOfMap)
+    );
+    assert_eq!(
+        core.do_command(CommandAttackUnit(TANK_ID, TANK_ID, None)),
+        Err(CommandError::CanNotAttackOwnUnit)
+    );
+    core.do_command(CommandEndTurn).unwrap();
+    // tank is out of soldier's sight range
+    assert_eq!(
+        core.do_command(CommandAttackUnit(SOLDIER_ID, TANK_ID, None)),
+        Err(CommandError::TargetIsNotVisible)
+    );
+}
+
+#[test]
+fn rejected_commands_do_not_produce_events() {
+    let mut core = new_core("duel.json");
+    let _ = get_events(&mut core);
+    let jump = CommandMove(TANK_ID, vec![pos(0, 1), pos(2, 1)]);
+    assert!(core.do_command(jump).is_err());
+    assert!(get_events(&mut core).is_empty());
+}
+
+#[test]
+fn moves_spend_move_points() {
+    let mut core = new_core("duel.json");
+    // plain tiles cost 2, tank has 10 move points,
+    // the bottom row is out of enemies' zones of control
+    let mut too_long = vec![pos(0, 1)];
+    too_long.extend((0..6).map(|x| pos(x, 2)));
+    assert_eq!(
+        core.do_command(CommandMove(TANK_ID, too_long)),
+        Err(CommandError::NotEnoughMovePoints)
+    );
+    let path = vec![pos(0, 1), pos(0, 2), pos(1, 2)];
+    core.do_command(CommandMove(TANK_ID, path)).unwrap();
+    let path = vec![pos(1, 2), pos(2, 2), pos(3, 2), pos(4, 2)];
+    core.do_command(CommandMove(TANK_ID, path)).unwrap();
+    let path = vec![pos(4, 2), pos(5, 2)];
+    assert_eq!(
+        core.do_command(CommandMove(TANK_ID, path.clone())),
+        Err(CommandError::NoMovePoints)
+    );
+    core.do_command(CommandEndTurn).unwrap();
+    core.do_command(CommandEndTurn).unwrap();
+    core.do_command(CommandMove(TANK_ID, path)).unwrap();
+}
+
+#[test]
+fn zone_of_control_ends_moves() {
+    let mut core = new_core("duel.json");
+    // 4,1 and 5,1 are next to the soldier
+    let path: Vec<_> = (0..6).map(|x| pos(x, 1)).collect();
+    assert_eq!(
+        core.do_command(CommandMove(TANK_ID, path)),
+        Err(CommandError::ZoneOfControl)
+    );
+    let path: Vec<_> = (0..5).map(|x| pos(x, 1)).collect();
+    core.do_command(CommandMove(TANK_ID, path)).unwrap();
+    core.do_command(CommandEndTurn).unwrap();
+    core.do_command(CommandEndTurn).unwrap();
+    let path = vec![pos(4, 1), pos(5, 1), pos(6, 1)];
+    assert_eq!(
+        core.do_command(CommandMove(TANK_ID, path)),
+        Err(CommandError::ZoneOfControl)
+    );
+    // units can leave the zone they started in
+    let path = vec![pos(4, 1), pos(3, 1), pos(2, 1)];
+    core.do_command(CommandMove(TANK_ID, path)).unwrap();
+}
+
+#[test]
+fn hidden_enemies_do_not_reject_paths() {
+    let mut core = new_core("hidden_blocker.json");
+    // the enemy at 7,0 is out of sight, so the path through it is accepted
+    let path: Vec<_> = (0..9).map(|x| pos(x, 0)).collect();
+    core.do_command(CommandMove(TANK_ID, path)).unwrap();
+    let _ = get_events(&mut core);
+    // the enemy is seen on the way and its zone of control stops the tank
+    let view = core.view(&PlayerId { id: 0 });
+    assert_eq!(view.units[&TANK_ID].pos, pos(6, 0));
+}
+
+#[test]
+fn unit_attacks_once_per_turn() {
+    let mut core = new_core("duel.json");
+    core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, None))
+        .unwrap();
+    let error = core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, None));
+    // the soldier may be already dead
+    assert!(error == Err(CommandError::AlreadyAttacked) || error == Err(CommandError::NoSuchUnit));
+}
+
+#[test]
+fn wounds_remove_models_one_by_one() {
+    let mut core = new_core("duel.json");
+    // tank has one model and fires one shot
+    let mut count = 4;
+    for _ in 0..100 {
+        core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, None))
+            .unwrap();
+        let _ = get_events(&mut core);
+        match core.view(&PlayerId { id: 0 }).units.get(&SOLDIER_ID) {
+            Some(soldier) => {
+                assert!(soldier.count == count || soldier.count == count - 1);
+                count = soldier.count;
+            }
+            None => {
+                assert_eq!(count, 1);
+                return;
+            }
+        }
+        core.do_command(CommandEndTurn).unwrap();
+        core.do_command(CommandEndTurn).unwrap();
+    }
+    panic!("Soldier survived 100 attacks");
+}
+
+#[test]
+fn attacks_need_line_of_sight() {
+    let mut core = new_core("line_of_sight.json");
+    // building is between
+    assert_eq!(
+        core.do_command(CommandAttackUnit(UnitId { id: 0 }, UnitId { id: 4 }, None)),
+        Err(CommandError::NoLineOfSight)
+    );
+    // own soldier is between
+    assert_eq!(
+        core.do_command(CommandAttackUnit(UnitId { id: 1 }, UnitId { id: 5 }, None)),
+        Err(CommandError::NoLineOfSight)
+    );
+    // tank on the hill fires over the forest
+    core.do_command(CommandAttackUnit(UnitId { id: 2 }, UnitId { id: 6 }, None))
+        .unwrap();
+}
+
+#[test]
+fn attack_uses_chosen_or_best_weapon() {
+    let mut core = new_core("duel.json");
+    assert_eq!(
+        core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, Some(2))),
+        Err(CommandError::NoSuchWeapon)
+    );
+    // machine gun is shorter ranged than the cannon
+    assert_eq!(
+        core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, Some(1))),
+        Err(CommandError::OutOfRange)
+    );
+    core.do_command(CommandMove(TANK_ID, vec![pos(0, 1), pos(1, 1)]))
+        .unwrap();
+    let _ = get_events(&mut core);
+    // machine gun is better against soldiers
+    core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, None))
+        .unwrap();
+    let weapon_indexes: Vec<_> = get_events(&mut core)
+        .iter()
+        .filter_map(|event| match *event {
+            EventAttackUnit(_, _, ref result) => Some(result.weapon_index),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(weapon_indexes, vec![1]);
+}
+
+#[test]
+fn turns_go_round_all_players() {
+    let mut core = new_core("three_players.json");
+    for &id in [1, 2, 0, 1].iter() {
+        core.do_command(CommandEndTurn).unwrap();
+        assert_eq!(core.player_id(), PlayerId { id });
+    }
+    // every player moves only its own units
+    core.do_command(CommandEndTurn).unwrap();
+    let move_red_tank = CommandMove(UnitId { id: 1 }, vec![pos(7, 0), pos(6, 0)]);
+    assert_eq!(
+        core.do_command(move_red_tank),
+        Err(CommandError::NotOwnUnit)
+    );
+    let move_green_tank = CommandMove(UnitId { id: 2 }, vec![pos(0, 2), pos(1, 2)]);
+    core.do_command(move_green_tank).unwrap();
+}
+
+#[test]
+fn same_seed_gives_same_events() {
+    fn play() -> Vec<String> {
+        let mut core = new_core("duel.json");
+        let mut events = Vec::new();
+        for _ in 0..10 {
+            let _ = core.do_command(CommandAttackUnit(TANK_ID, SOLDIER_ID, None));
+            core.do_command(CommandEndTurn).unwrap();
+            events.extend(get_events(&mut core).iter().map(to_json));
+            let _ = core.do_command(CommandEndTurn);
+        }
+        events
+    }
+    assert_eq!(play(), play());
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
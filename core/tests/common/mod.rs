@@ -0,0 +1,48 @@
+// See LICENSE file for copyright and license details.
+
+#![allow(dead_code)]
+
+use cgmath::Vector2;
+use marauder_core::core::{Core, Event};
+use marauder_core::fs::FileSystem;
+use marauder_core::types::{MInt, MapPos};
+use std::path::{Path, PathBuf};
+
+pub const SEED: u64 = 1;
+
+// Object types are shared with the game
+pub fn get_fs() -> FileSystem {
+    FileSystem::with_root(&Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+}
+
+pub fn get_scenario_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/data")
+        .join(name)
+}
+
+pub fn new_core(scenario_name: &str) -> Core {
+    Core::new(&get_fs(), &get_scenario_path(scenario_name), Some(SEED))
+}
+
+pub fn pos(x: MInt, y: MInt) -> MapPos {
+    MapPos {
+        v: Vector2 { x, y },
+    }
+}
+
+// Events of the current player
+pub fn get_events(core: &mut Core) -> Vec<Event> {
+    let mut events = Vec::new();
+    while let Some(event) = core.get_event() {
+        events.push(event);
+    }
+    events
+}
+
+// Events are compared by their serialized form
+pub fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,70 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_events, new_core, pos};
+use marauder_core::core::Command::{CommandEndTurn, CommandMove};
+use marauder_core::core::Event;
+use marauder_core::core::Event::{EventCreateUnit, EventEndTurn, EventMove, EventShowUnit};
+use marauder_core::types::{PlayerId, UnitId};
+
+fn is_end_turn(event: &Event, from: i32, to: i32) -> bool {
+    match *event {
+        EventEndTurn(ref old_id, ref new_id) => old_id.id == from && new_id.id == to,
+        _ => false,
+    }
+}
+
+fn is_create_unit(event: &Event, unit_id: UnitId) -> bool {
+    match *event {
+        EventCreateUnit(id, _, _, _) => id == unit_id,
+        _ => false,
+    }
+}
+
+#[test]
+fn hidden_units_are_not_reported() {
+    let mut core = new_core("fow.json");
+    let events = get_events(&mut core);
+    assert_eq!(events.len(), 1);
+    assert!(is_create_unit(&events[0], UnitId { id: 0 }));
+    core.do_command(CommandEndTurn).unwrap();
+    let events = get_events(&mut core);
+    assert_eq!(events.len(), 2);
+    assert!(is_create_unit(&events[0], UnitId { id: 1 }));
+    assert!(is_end_turn(&events[1], 0, 1));
+}
+
+#[test]
+fn enemy_move_is_shown_only_in_sight() {
+    let mut core = new_core("fow.json");
+    let _ = get_events(&mut core);
+    core.do_command(CommandEndTurn).unwrap();
+    let _ = get_events(&mut core);
+    // tank's sight range is 5, so only the last two tiles are visible
+    let path: Vec<_> = (4..10).rev().map(|x| pos(x, 0)).collect();
+    core.do_command(CommandMove(UnitId { id: 1 }, path))
+        .unwrap();
+    core.do_command(CommandEndTurn).unwrap();
+    let events = get_events(&mut core);
+    assert_eq!(events.len(), 4);
+    assert!(is_end_turn(&events[0], 0, 1));
+    match events[1] {
+        EventShowUnit(ref unit) => {
+            assert_eq!(unit.id, UnitId { id: 1 });
+            assert_eq!(unit.pos, pos(5, 0));
+            assert_eq!(unit.player_id, PlayerId { id: 1 });
+        }
+        _ => panic!("Expected EventShowUnit"),
+    }
+    match events[2] {
+        EventMove(unit_id, ref path) => {
+            assert_eq!(unit_id, UnitId { id: 1 });
+            assert_eq!(path, &[pos(5, 0), pos(4, 0)]);
+        }
+        _ => panic!("Expected EventMove"),
+    }
+    assert!(is_end_turn(&events[3], 1, 0));
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
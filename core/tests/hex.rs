@@ -0,0 +1,100 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::pos;
+use marauder_core::dir::{Dir, DIR_TO_POS_DIFF};
+use marauder_core::hex::{self, CubePos};
+use marauder_core::types::{MInt, MapPos};
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+// Offset tables work only for positions on the board
+fn map_pos() -> impl Strategy<Value = MapPos> {
+    (0..200, 0..200).prop_map(|(x, y)| pos(x, y))
+}
+
+fn any_pos() -> impl Strategy<Value = MapPos> {
+    (-200..200, -200..200).prop_map(|(x, y)| pos(x, y))
+}
+
+fn to_set(tiles: &[MapPos]) -> HashSet<(MInt, MInt)> {
+    tiles.iter().map(|p| (p.v.x, p.v.y)).collect()
+}
+
+proptest! {
+    #[test]
+    fn cube_pos_round_trip(p in any_pos()) {
+        let cube = CubePos::from_map_pos(p);
+        prop_assert_eq!(cube.x + cube.y + cube.z, 0);
+        prop_assert_eq!(cube.to_map_pos(), p);
+    }
+
+    #[test]
+    fn neighbours_match_offset_table(p in map_pos()) {
+        for (i, n) in hex::neighbours(p).into_iter().enumerate() {
+            let dir = Dir::from_int(i as MInt);
+            let diff = DIR_TO_POS_DIFF[(p.v.y % 2) as usize][i];
+            prop_assert_eq!(n, MapPos { v: p.v + diff });
+            prop_assert_eq!(Dir::get_dir_from_to(p, n).to_int(), dir.to_int());
+            prop_assert_eq!(hex::distance(p, n), 1);
+        }
+    }
+
+    #[test]
+    fn ring_is_closed_loop_at_radius(c in any_pos(), r in 0..20) {
+        let tiles = hex::ring(c, r);
+        prop_assert_eq!(tiles.len() as MInt, std::cmp::max(1, 6 * r));
+        prop_assert_eq!(to_set(&tiles).len(), tiles.len());
+        for (i, &p) in tiles.iter().enumerate() {
+            prop_assert_eq!(hex::distance(c, p), r);
+            let next = tiles[(i + 1) % tiles.len()];
+            if r > 0 {
+                prop_assert_eq!(hex::distance(p, next), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_and_range_cover_the_same_area(c in any_pos(), r in 0..20) {
+        let spiral = hex::spiral(c, r);
+        let range = hex::range(c, r);
+        prop_assert_eq!(spiral[0], c);
+        prop_assert_eq!(range.len() as MInt, 3 * r * (r + 1) + 1);
+        prop_assert_eq!(spiral.len(), range.len());
+        prop_assert_eq!(to_set(&spiral), to_set(&range));
+        for &p in range.iter() {
+            prop_assert!(hex::distance(c, p) <= r);
+        }
+    }
+
+    #[test]
+    fn line_is_connected_and_straight(a in any_pos(), b in any_pos()) {
+        let tiles = hex::line(a, b);
+        let n = hex::distance(a, b);
+        prop_assert_eq!(tiles.len() as MInt, n + 1);
+        prop_assert_eq!(tiles[0], a);
+        prop_assert_eq!(*tiles.last().unwrap(), b);
+        for (i, &p) in tiles.iter().enumerate() {
+            prop_assert_eq!(hex::distance(a, p), i as MInt);
+            prop_assert_eq!(hex::distance(p, b), n - i as MInt);
+        }
+    }
+
+    #[test]
+    fn rotation_keeps_distance(p in any_pos(), c in any_pos(), steps in -12..12) {
+        let rotated = hex::rotate(p, c, steps);
+        prop_assert_eq!(hex::distance(c, rotated), hex::distance(c, p));
+        prop_assert_eq!(hex::rotate(rotated, c, -steps), p);
+        prop_assert_eq!(hex::rotate(p, c, 6), p);
+    }
+
+    #[test]
+    fn rotation_follows_dir_order(c in any_pos(), i in 0..6) {
+        let n = hex::neighbour(c, Dir::from_int(i));
+        let next = hex::neighbour(c, Dir::from_int((i + 1) % 6));
+        prop_assert_eq!(hex::rotate(n, c, 1), next);
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
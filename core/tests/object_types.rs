@@ -0,0 +1,30 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::get_fs;
+use marauder_core::core::{ObjectTypes, UnitClass};
+use std::path::Path;
+
+#[test]
+fn unit_types_are_loaded_from_file() {
+    let object_types = ObjectTypes::new(&get_fs().get(Path::new("data/object_types.json")));
+    let names: Vec<&str> = object_types
+        .unit_types()
+        .iter()
+        .map(|unit_type| unit_type.name.as_str())
+        .collect();
+    assert_eq!(names, ["tank", "soldier"]);
+    let soldier = &object_types.unit_types()[1];
+    assert!(soldier.class == UnitClass::Infantry);
+    assert_eq!(soldier.count, 4);
+}
+
+#[test]
+#[should_panic(expected = "refers to unknown weapon type \"laser\"")]
+fn unknown_weapon_is_rejected() {
+    let path = get_fs().get(Path::new("core/tests/data/unknown_weapon.json"));
+    let _ = ObjectTypes::new(&path);
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,109 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_events, get_fs, get_scenario_path, new_core, pos};
+use marauder_core::core::Command::{
+    CommandAttackUnit, CommandEndTurn, CommandMove, CommandOverwatch,
+};
+use marauder_core::core::Event::{EventAttackUnit, EventMove};
+use marauder_core::core::{CommandError, Core};
+use marauder_core::types::{PlayerId, UnitId};
+
+const BLUE_TANK_ID: UnitId = UnitId { id: 0 };
+const RED_TANK_ID: UnitId = UnitId { id: 1 };
+
+#[test]
+fn overwatch_reserves_attack_till_next_turn() {
+    let mut core = new_core("duel.json");
+    core.do_command(CommandOverwatch(BLUE_TANK_ID)).unwrap();
+    assert_eq!(
+        core.do_command(CommandOverwatch(BLUE_TANK_ID)),
+        Err(CommandError::OnOverwatch)
+    );
+    let soldier_id = UnitId { id: 1 };
+    assert_eq!(
+        core.do_command(CommandAttackUnit(BLUE_TANK_ID, soldier_id, None)),
+        Err(CommandError::OnOverwatch)
+    );
+    let path = vec![pos(0, 1), pos(1, 1)];
+    assert_eq!(
+        core.do_command(CommandMove(BLUE_TANK_ID, path)),
+        Err(CommandError::OnOverwatch)
+    );
+    core.do_command(CommandEndTurn).unwrap();
+    core.do_command(CommandEndTurn).unwrap();
+    let _ = get_events(&mut core);
+    let tank = &core.view(&PlayerId { id: 0 }).units[&BLUE_TANK_ID];
+    assert!(!tank.overwatch);
+}
+
+#[test]
+fn reaction_fire_interrupts_move() {
+    let (mut hits, mut misses) = (0, 0);
+    for seed in 0..20 {
+        let path = get_scenario_path("overwatch.json");
+        let mut core = Core::new(&get_fs(), &path, Some(seed));
+        core.do_command(CommandOverwatch(BLUE_TANK_ID)).unwrap();
+        core.do_command(CommandEndTurn).unwrap();
+        let _ = get_events(&mut core);
+        let path: Vec<_> = (4..10).rev().map(|x| pos(x, 0)).collect();
+        core.do_command(CommandMove(RED_TANK_ID, path)).unwrap();
+        let mut last_pos = pos(9, 0);
+        let mut attacks = Vec::new();
+        for event in get_events(&mut core).into_iter() {
+            match event {
+                EventMove(_, ref path) => {
+                    // no moves after the tank is destroyed
+                    assert!(attacks.iter().all(|&killed_count| killed_count == 0));
+                    last_pos = *path.last().unwrap();
+                }
+                EventAttackUnit(attacker_id, _, ref result) => {
+                    assert_eq!(attacker_id, BLUE_TANK_ID);
+                    // the first tile in blue tank's sight and cannon range
+                    assert_eq!(last_pos, pos(5, 0));
+                    attacks.push(result.killed_count());
+                }
+                _ => {}
+            }
+        }
+        // overwatching unit fires only once
+        assert_eq!(attacks.len(), 1);
+        if attacks[0] > 0 {
+            hits += 1;
+        } else {
+            assert_eq!(last_pos, pos(4, 0));
+            misses += 1;
+        }
+    }
+    assert!(hits > 0 && misses > 0);
+}
+
+#[test]
+fn squad_that_loses_models_goes_on() {
+    let soldier_id = UnitId { id: 1 };
+    let mut hits = 0;
+    for seed in 0..20 {
+        let path = get_scenario_path("squad_overwatch.json");
+        let mut core = Core::new(&get_fs(), &path, Some(seed));
+        core.do_command(CommandOverwatch(BLUE_TANK_ID)).unwrap();
+        core.do_command(CommandEndTurn).unwrap();
+        let _ = get_events(&mut core);
+        let path: Vec<_> = (2..8).rev().map(|x| pos(x, 0)).collect();
+        core.do_command(CommandMove(soldier_id, path)).unwrap();
+        let mut killed_count = 0;
+        for event in get_events(&mut core).into_iter() {
+            if let EventAttackUnit(_, _, ref result) = event {
+                killed_count += result.killed_count();
+            }
+        }
+        let soldier = &core.view(&PlayerId { id: 1 }).units[&soldier_id];
+        assert_eq!(soldier.pos, pos(2, 0));
+        if killed_count > 0 {
+            hits += 1;
+        }
+    }
+    assert!(hits > 0);
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,123 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_fs, pos};
+use marauder_core::core::{ObjectTypes, Unit, UnitTypeId};
+use marauder_core::game_state::GameState;
+use marauder_core::map::{distance, MapPosIter, TerrainMap};
+use marauder_core::pathfinder::Pathfinder;
+use marauder_core::types::{MInt, MapPos, PlayerId, Size2, UnitId};
+use std::path::Path;
+
+const MAP_SIZE: Size2<MInt> = Size2 { w: 8, h: 5 };
+const TANK_TYPE_ID: UnitTypeId = UnitTypeId { id: 0 };
+const SOLDIER_TYPE_ID: UnitTypeId = UnitTypeId { id: 1 };
+
+fn get_object_types() -> ObjectTypes {
+    ObjectTypes::new(&get_fs().get(Path::new("data/object_types.json")))
+}
+
+fn get_state(object_types: &ObjectTypes, type_id: UnitTypeId, start_pos: MapPos) -> GameState {
+    let rows: Vec<String> = vec!["..f..#..", "=====f==", ".~~..#..", ".~~.ff..", "........"]
+        .into_iter()
+        .map(|row| row.to_string())
+        .collect();
+    let map = TerrainMap::from_rows(object_types, MAP_SIZE, &rows);
+    let mut state = GameState::new(map);
+    let id = UnitId { id: 0 };
+    let _ = state.units.insert(
+        id,
+        Unit {
+            id,
+            pos: start_pos,
+            player_id: PlayerId { id: 0 },
+            type_id,
+            move_points: 100,
+            attacked: false,
+            overwatch: false,
+            count: 1,
+        },
+    );
+    state
+}
+
+fn is_reachable(object_types: &ObjectTypes, state: &GameState, destination: MapPos) -> bool {
+    let unit = &state.units[&UnitId { id: 0 }];
+    let mut pathfinder = Pathfinder::new(MAP_SIZE);
+    pathfinder.fill_map(object_types, state, unit);
+    pathfinder.get_map().tile(destination).parent.is_some()
+}
+
+#[test]
+fn find_path_agrees_with_fill_map() {
+    let object_types = get_object_types();
+    for &type_id in [TANK_TYPE_ID, SOLDIER_TYPE_ID].iter() {
+        let state = get_state(&object_types, type_id, pos(0, 0));
+        let unit = &state.units[&UnitId { id: 0 }];
+        let mut pathfinder = Pathfinder::new(MAP_SIZE);
+        pathfinder.fill_map(&object_types, &state, unit);
+        let mut costs = Vec::new();
+        for destination in MapPosIter::new(MAP_SIZE) {
+            let tile = pathfinder.get_map().tile(destination);
+            costs.push(tile.parent.map(|_| tile.cost));
+        }
+        for (destination, cost) in MapPosIter::new(MAP_SIZE).zip(costs) {
+            if destination == unit.pos {
+                continue;
+            }
+            let path = pathfinder.find_path(&object_types, &state, unit, destination);
+            let path_cost = path.map(|_| pathfinder.get_map().tile(destination).cost);
+            assert_eq!(path_cost, cost, "destination {:?}", destination.v);
+        }
+    }
+}
+
+#[test]
+fn water_is_impassable() {
+    let object_types = get_object_types();
+    let state = get_state(&object_types, SOLDIER_TYPE_ID, pos(0, 0));
+    assert!(!is_reachable(&object_types, &state, pos(1, 2)));
+}
+
+#[test]
+fn buildings_are_impassable_for_vehicles() {
+    let object_types = get_object_types();
+    let state = get_state(&object_types, TANK_TYPE_ID, pos(0, 0));
+    assert!(!is_reachable(&object_types, &state, pos(5, 0)));
+    let state = get_state(&object_types, SOLDIER_TYPE_ID, pos(0, 0));
+    assert!(is_reachable(&object_types, &state, pos(5, 0)));
+}
+
+#[test]
+fn paths_end_in_enemy_zone_of_control() {
+    let object_types = get_object_types();
+    let mut state = get_state(&object_types, SOLDIER_TYPE_ID, pos(0, 0));
+    let enemy_pos = pos(2, 4);
+    let mut enemy = state.units[&UnitId { id: 0 }].clone();
+    enemy.id = UnitId { id: 1 };
+    enemy.player_id = PlayerId { id: 1 };
+    enemy.pos = enemy_pos;
+    let _ = state.units.insert(enemy.id, enemy);
+    let unit = &state.units[&UnitId { id: 0 }];
+    let mut pathfinder = Pathfinder::new(MAP_SIZE);
+    pathfinder.fill_map(&object_types, &state, unit);
+    for destination in MapPosIter::new(MAP_SIZE) {
+        let path = match pathfinder.get_path(destination) {
+            Some(ref path) if path.len() > 1 => path.clone(),
+            _ => continue,
+        };
+        let is_next_to_enemy = distance(destination, enemy_pos) == 1;
+        assert_eq!(
+            pathfinder.get_map().tile(destination).is_stop,
+            is_next_to_enemy
+        );
+        for &pos in path[1..path.len() - 1].iter() {
+            assert!(distance(pos, enemy_pos) > 1);
+        }
+    }
+    // the tile behind the enemy is reached around its zone
+    assert!(pathfinder.get_path(pos(4, 4)).is_some());
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
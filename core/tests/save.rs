@@ -0,0 +1,106 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_events, get_fs, new_core, pos, to_json};
+use marauder_core::core::Command::{CommandAttackUnit, CommandEndTurn, CommandMove};
+use marauder_core::core::Event::{EventGameOver, EventShowUnit};
+use marauder_core::core::{Command, CommandError, Core};
+use marauder_core::replay::Replay;
+use marauder_core::types::{PlayerId, UnitId};
+use std::env;
+
+fn get_commands() -> Vec<Command> {
+    vec![
+        CommandAttackUnit(UnitId { id: 0 }, UnitId { id: 1 }, None),
+        CommandEndTurn,
+        CommandEndTurn,
+        CommandMove(UnitId { id: 0 }, vec![pos(1, 1), pos(2, 1)]),
+    ]
+}
+
+fn play(core: &mut Core, commands: Vec<Command>) -> Vec<String> {
+    let mut events = Vec::new();
+    for command in commands {
+        let _ = core.do_command(command);
+        events.extend(get_events(core).iter().map(to_json));
+    }
+    events
+}
+
+#[test]
+fn loaded_game_continues_the_same_way() {
+    let save_path = env::temp_dir().join("marauder_core_test_save.json");
+    let mut core = new_core("duel.json");
+    let _ = get_events(&mut core);
+    let path = vec![pos(0, 1), pos(1, 1)];
+    core.do_command(CommandMove(UnitId { id: 0 }, path))
+        .unwrap();
+    let _ = get_events(&mut core);
+    core.save(&save_path);
+    let mut loaded_core = Core::load(&get_fs(), &save_path);
+    // loaded game starts with a snapshot of what the player has seen
+    let snapshot = get_events(&mut loaded_core);
+    assert_eq!(snapshot.len(), 2);
+    for event in snapshot.iter() {
+        match *event {
+            EventShowUnit(_) => {}
+            _ => panic!("Expected EventShowUnit"),
+        }
+    }
+    assert_eq!(
+        play(&mut core, get_commands()),
+        play(&mut loaded_core, get_commands())
+    );
+}
+
+#[test]
+fn loaded_finished_game_stays_over() {
+    let save_path = env::temp_dir().join("marauder_core_test_save_game_over.json");
+    let mut core = new_core("turn_limit.json");
+    for _ in 0..4 {
+        core.do_command(CommandEndTurn).unwrap();
+    }
+    assert!(core.game_result().is_some());
+    let _ = get_events(&mut core);
+    core.save(&save_path);
+    let mut loaded_core = Core::load(&get_fs(), &save_path);
+    {
+        let result = loaded_core.game_result().unwrap();
+        assert_eq!(result.winner, Some(PlayerId { id: 1 }));
+        assert_eq!(result.turns, 2);
+    }
+    match get_events(&mut loaded_core).last() {
+        Some(EventGameOver(_)) => {}
+        _ => panic!("Expected EventGameOver"),
+    }
+    assert_eq!(
+        loaded_core.do_command(CommandEndTurn),
+        Err(CommandError::GameIsOver)
+    );
+}
+
+#[test]
+fn replay_has_all_core_events() {
+    let replay_path = env::temp_dir().join("marauder_core_test_replay.json");
+    let mut core = new_core("fow.json");
+    core.do_command(CommandEndTurn).unwrap();
+    core.replay().save(&replay_path);
+    let replay = Replay::load(&replay_path);
+    assert_eq!(replay.seed, common::SEED);
+    // two units are created and one turn is ended
+    assert_eq!(replay.events().len(), 3);
+    assert_eq!(replay.log.len(), 4);
+}
+
+#[test]
+fn rejected_commands_are_not_logged() {
+    let mut core = new_core("fow.json");
+    let log_len = core.replay().log.len();
+    // unit 1 belongs to the other player
+    let command = CommandMove(UnitId { id: 1 }, vec![pos(9, 0), pos(8, 0)]);
+    assert!(core.do_command(command).is_err());
+    assert_eq!(core.replay().log.len(), log_len);
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
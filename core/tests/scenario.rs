@@ -0,0 +1,33 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_fs, get_scenario_path};
+use marauder_core::core::Core;
+use marauder_core::core::Event::EventCreateUnit;
+use marauder_core::scenario::Scenario;
+use marauder_core::types::PlayerId;
+use std::path::Path;
+
+#[test]
+fn scenario_units_are_created() {
+    let mut core = Core::new(&get_fs(), Path::new("data/scenarios/default.json"), None);
+    let names: Vec<&str> = core.players().iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Blue", "Red"]);
+    let mut owners = Vec::new();
+    while let Some(event) = core.get_event() {
+        if let EventCreateUnit(_, _, _, player_id) = event {
+            owners.push(player_id);
+        }
+    }
+    let expected = [0, 0, 1, 1].iter().map(|&id| PlayerId { id });
+    assert!(owners.into_iter().eq(expected));
+}
+
+#[test]
+#[should_panic(expected = "unit 1: position Vector2 { x: 0, y: 0 } is occupied")]
+fn units_on_one_tile_are_rejected() {
+    let _ = Scenario::new(&get_scenario_path("occupied_tile.json"));
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,148 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_fs, new_core, pos, to_json};
+use marauder_core::core::Command::{CommandAttackUnit, CommandEndTurn, CommandMove};
+use marauder_core::core::Event::{EventEndTurn, EventMove, EventShowUnit};
+use marauder_core::core::{CommandError, Event};
+use marauder_core::game::Game;
+use marauder_core::net::RemoteGame;
+use marauder_core::server::Server;
+use marauder_core::types::{MapPos, PlayerId, UnitId};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+const BLUE: PlayerId = PlayerId { id: 0 };
+const RED: PlayerId = PlayerId { id: 1 };
+
+// Returns address of the server
+fn start_server(scenario_name: &str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let core = new_core(scenario_name);
+    let _ = thread::spawn(move || Server::new(core, listener).run(&mut |_| {}));
+    address
+}
+
+fn connect(address: &str, player_id: Option<PlayerId>) -> RemoteGame {
+    RemoteGame::connect(&get_fs(), address, player_id).unwrap()
+}
+
+// Server learns that the old connection is closed a bit later,
+// until then the seat is still taken
+fn reconnect(address: &str, player_id: PlayerId) -> RemoteGame {
+    for _ in 0..100 {
+        if let Ok(game) = RemoteGame::connect(&get_fs(), address, Some(player_id.clone())) {
+            return game;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("Can not reconnect player {}", player_id.id);
+}
+
+// Answer to a command comes after all earlier events,
+// so a rejected command waits for everything the server has sent
+fn take_events(game: &mut RemoteGame) -> Vec<Event> {
+    let bad_attack = CommandAttackUnit(UnitId { id: -1 }, UnitId { id: -1 }, None);
+    assert!(game.do_command(bad_attack).is_err());
+    let mut events = Vec::new();
+    while let Some(event) = game.get_event() {
+        events.push(event);
+    }
+    events
+}
+
+fn get_shown_units(events: &[Event]) -> Vec<(UnitId, MapPos)> {
+    events
+        .iter()
+        .map(|event| match event {
+            EventShowUnit(ref unit) => (unit.id, unit.pos),
+            _ => panic!("Expected EventShowUnit"),
+        })
+        .collect()
+}
+
+fn tank_path() -> Vec<MapPos> {
+    (4..10).rev().map(|x| pos(x, 0)).collect()
+}
+
+#[test]
+fn players_get_only_own_events() {
+    let address = start_server("fow.json");
+    let mut blue = connect(&address, None);
+    let mut red = connect(&address, None);
+    assert_eq!(blue.player_id(), BLUE);
+    assert_eq!(red.player_id(), RED);
+    assert!(RemoteGame::connect(&get_fs(), &address, None).is_err());
+    let blue_units = get_shown_units(&take_events(&mut blue));
+    assert_eq!(blue_units, vec![(UnitId { id: 0 }, pos(0, 0))]);
+    let red_units = get_shown_units(&take_events(&mut red));
+    assert_eq!(red_units, vec![(UnitId { id: 1 }, pos(9, 0))]);
+    assert_eq!(
+        red.do_command(CommandEndTurn),
+        Err(CommandError::NotYourTurn)
+    );
+    blue.do_command(CommandEndTurn).unwrap();
+    let end_turn = to_json(&vec![EventEndTurn(BLUE, RED)]);
+    assert_eq!(to_json(&take_events(&mut blue)), end_turn);
+    assert_eq!(to_json(&take_events(&mut red)), end_turn);
+    assert_eq!(red.current_player_id(), RED);
+    red.do_command(CommandMove(UnitId { id: 1 }, tank_path()))
+        .unwrap();
+    // moves are done step by step, blue tank is spotted on the way
+    let (moves, shows): (Vec<Event>, Vec<Event>) = take_events(&mut red)
+        .into_iter()
+        .partition(|event| matches!(*event, EventMove(_, _)));
+    let steps: Vec<Event> = tank_path()
+        .windows(2)
+        .map(|step| EventMove(UnitId { id: 1 }, step.to_vec()))
+        .collect();
+    assert_eq!(to_json(&moves), to_json(&steps));
+    assert_eq!(get_shown_units(&shows), vec![(UnitId { id: 0 }, pos(0, 0))]);
+    // only the end of the move is in blue's sight
+    let events = take_events(&mut blue);
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        get_shown_units(&events[..1]),
+        vec![(UnitId { id: 1 }, pos(5, 0))]
+    );
+    let visible_move = EventMove(UnitId { id: 1 }, vec![pos(5, 0), pos(4, 0)]);
+    assert_eq!(to_json(&events[1]), to_json(&visible_move));
+}
+
+#[test]
+fn reconnected_player_gets_its_view() {
+    let address = start_server("fow.json");
+    let mut blue = connect(&address, None);
+    let mut red = connect(&address, None);
+    blue.do_command(CommandEndTurn).unwrap();
+    drop(blue);
+    red.do_command(CommandMove(UnitId { id: 1 }, tank_path()))
+        .unwrap();
+    // events sent while blue was away are part of the new view
+    let mut blue = reconnect(&address, BLUE);
+    assert_eq!(blue.player_id(), BLUE);
+    assert_eq!(blue.current_player_id(), RED);
+    let expected = vec![(UnitId { id: 0 }, pos(0, 0)), (UnitId { id: 1 }, pos(4, 0))];
+    assert_eq!(get_shown_units(&take_events(&mut blue)), expected);
+    red.do_command(CommandEndTurn).unwrap();
+    let end_turn = to_json(&vec![EventEndTurn(RED, BLUE)]);
+    assert_eq!(to_json(&take_events(&mut blue)), end_turn);
+}
+
+#[test]
+fn occupied_seat_can_not_be_taken() {
+    let address = start_server("fow.json");
+    let mut blue = connect(&address, None);
+    let _red = connect(&address, None);
+    assert!(RemoteGame::connect(&get_fs(), &address, Some(BLUE)).is_err());
+    // the old connection keeps playing
+    let _ = take_events(&mut blue);
+    blue.do_command(CommandEndTurn).unwrap();
+    let end_turn = to_json(&vec![EventEndTurn(BLUE, RED)]);
+    assert_eq!(to_json(&take_events(&mut blue)), end_turn);
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
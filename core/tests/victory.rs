@@ -0,0 +1,78 @@
+// See LICENSE file for copyright and license details.
+
+mod common;
+
+use common::{get_events, new_core};
+use marauder_core::core::Command::{CommandAttackUnit, CommandEndTurn};
+use marauder_core::core::CommandError;
+use marauder_core::core::Event::EventGameOver;
+use marauder_core::types::{PlayerId, UnitId};
+use marauder_core::victory::VictoryReason;
+
+#[test]
+fn objective_held_wins() {
+    let mut core = new_core("objectives.json");
+    core.do_command(CommandEndTurn).unwrap();
+    core.do_command(CommandEndTurn).unwrap();
+    assert!(core.game_result().is_none());
+    core.do_command(CommandEndTurn).unwrap();
+    {
+        let result = core.game_result().unwrap();
+        assert_eq!(result.winner, Some(PlayerId { id: 0 }));
+        assert_eq!(result.reason, VictoryReason::ObjectivesHeld);
+        assert_eq!(result.stats[0].victory_points, 2);
+    }
+    match get_events(&mut core).last() {
+        Some(EventGameOver(_)) => {}
+        _ => panic!("Expected EventGameOver"),
+    }
+}
+
+#[test]
+fn commands_are_rejected_after_game_over() {
+    let mut core = new_core("objectives.json");
+    for _ in 0..3 {
+        core.do_command(CommandEndTurn).unwrap();
+    }
+    assert_eq!(
+        core.do_command(CommandEndTurn),
+        Err(CommandError::GameIsOver)
+    );
+}
+
+#[test]
+fn turn_limit_gives_win_on_points() {
+    let mut core = new_core("turn_limit.json");
+    for _ in 0..3 {
+        core.do_command(CommandEndTurn).unwrap();
+        assert!(core.game_result().is_none());
+    }
+    core.do_command(CommandEndTurn).unwrap();
+    let result = core.game_result().unwrap();
+    assert_eq!(result.reason, VictoryReason::TurnLimit);
+    assert_eq!(result.winner, Some(PlayerId { id: 1 }));
+    assert_eq!(result.turns, 2);
+}
+
+#[test]
+fn destroying_all_enemies_wins() {
+    let mut core = new_core("last_unit.json");
+    let attack = CommandAttackUnit(UnitId { id: 0 }, UnitId { id: 1 }, None);
+    let mut turns = 0;
+    while core.game_result().is_none() {
+        turns += 1;
+        assert!(turns < 100);
+        core.do_command(attack.clone()).unwrap();
+        if core.game_result().is_none() {
+            core.do_command(CommandEndTurn).unwrap();
+            core.do_command(CommandEndTurn).unwrap();
+        }
+    }
+    let result = core.game_result().unwrap();
+    assert_eq!(result.winner, Some(PlayerId { id: 0 }));
+    assert_eq!(result.reason, VictoryReason::EnemiesDestroyed);
+    assert_eq!(result.stats[0].units_killed, 1);
+    assert_eq!(result.stats[1].units_lost, 1);
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
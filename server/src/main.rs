@@ -0,0 +1,64 @@
+// See LICENSE file for copyright and license details.
+
+#![warn(non_upper_case_globals)]
+#![warn(unused_results)]
+
+extern crate marauder_core;
+
+use marauder_core::core::Core;
+use marauder_core::fs::FileSystem;
+use marauder_core::server::Server;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+struct Args {
+    scenario_path: PathBuf,
+    seed: Option<u64>,
+    address: String,
+}
+
+// Usage: marauder_server [--seed <n>] [--address <host:port>] [scenario_path]
+fn parse_args() -> Args {
+    let mut args = Args {
+        scenario_path: PathBuf::from("data/scenarios/default.json"),
+        seed: None,
+        address: "127.0.0.1:7777".to_string(),
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--seed" {
+            let seed = match iter.next() {
+                Some(seed) => seed,
+                None => panic!("--seed: missing value"),
+            };
+            args.seed = match seed.parse() {
+                Ok(seed) => Some(seed),
+                Err(msg) => panic!("--seed: bad value '{}': {}", seed, msg),
+            };
+        } else if arg == "--address" {
+            args.address = match iter.next() {
+                Some(address) => address,
+                None => panic!("--address: missing value"),
+            };
+        } else {
+            args.scenario_path = PathBuf::from(arg);
+        }
+    }
+    args
+}
+
+fn main() {
+    let args = parse_args();
+    let core = Core::new(&FileSystem::new(), &args.scenario_path, args.seed);
+    let listener = match TcpListener::bind(&args.address) {
+        Ok(listener) => listener,
+        Err(err) => panic!("Can not listen on {}: {}", args.address, err),
+    };
+    println!("Seed: {}", core.seed());
+    println!("Listening on {}", args.address);
+    let mut server = Server::new(core, listener);
+    server.run(&mut |message| println!("{}", message));
+    println!("Game over");
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
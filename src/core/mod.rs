@@ -1,13 +0,0 @@
-// See LICENSE file for copyright and license details.
-
-pub mod conf;
-pub mod core;
-pub mod dir;
-pub mod fs;
-pub mod game_state;
-pub mod map;
-pub mod misc;
-pub mod pathfinder;
-pub mod types;
-
-// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
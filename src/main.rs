@@ -6,19 +6,74 @@
 extern crate cgmath;
 extern crate gl;
 extern crate glfw;
+extern crate marauder_core;
 extern crate rand;
 extern crate stb_image;
 extern crate stb_tt;
 extern crate time;
 extern crate error_context;
 
+use marauder_core::types::PlayerId;
+use std::path::PathBuf;
 use visualizer::visualizer::Visualizer;
 
-mod core;
 mod visualizer;
 
+struct Args {
+    scenario_path: PathBuf,
+    seed: Option<u64>,
+    server_address: Option<String>,
+    player_id: Option<PlayerId>,
+}
+
+// Usage: marauder [--seed <n>] [--connect <host:port> [--player <n>]] [scenario_path]
+fn parse_args() -> Args {
+    let mut args = Args {
+        scenario_path: PathBuf::from("data/scenarios/default.json"),
+        seed: None,
+        server_address: None,
+        player_id: None,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--seed" {
+            let seed = match iter.next() {
+                Some(seed) => seed,
+                None => panic!("--seed: missing value"),
+            };
+            args.seed = match seed.parse() {
+                Ok(seed) => Some(seed),
+                Err(msg) => panic!("--seed: bad value '{}': {}", seed, msg),
+            };
+        } else if arg == "--connect" {
+            args.server_address = match iter.next() {
+                Some(address) => Some(address),
+                None => panic!("--connect: missing value"),
+            };
+        } else if arg == "--player" {
+            let id = match iter.next() {
+                Some(id) => id,
+                None => panic!("--player: missing value"),
+            };
+            args.player_id = match id.parse() {
+                Ok(id) => Some(PlayerId { id }),
+                Err(msg) => panic!("--player: bad value '{}': {}", id, msg),
+            };
+        } else {
+            args.scenario_path = PathBuf::from(arg);
+        }
+    }
+    args
+}
+
 fn main() {
-    let mut visualizer = Visualizer::new();
+    let args = parse_args();
+    let mut visualizer = Visualizer::new(
+        &args.scenario_path,
+        args.seed,
+        args.server_address,
+        args.player_id,
+    );
     while visualizer.is_running() {
         visualizer.tick();
     }
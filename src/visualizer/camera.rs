@@ -1,10 +1,10 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::misc::{clamp, deg_to_rad};
-use crate::core::types::{MInt, Size2};
 use crate::visualizer::mgl;
+use crate::visualizer::misc::{clamp, deg_to_rad};
 use crate::visualizer::types::{MFloat, WorldPos};
 use cgmath::{deg, Matrix4, Vector, Vector3};
+use marauder_core::types::{MInt, Size2};
 
 pub struct Camera {
     x_angle: MFloat,
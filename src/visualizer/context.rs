@@ -1,13 +1,13 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::conf::Config;
-use crate::core::types::{MInt, Size2};
 use crate::visualizer::font_stash::FontStash;
 use crate::visualizer::mgl;
 use crate::visualizer::shader::Shader;
 use crate::visualizer::types::{ColorId, MatId, ScreenPos};
 use cgmath::Vector2;
 use glfw::WindowEvent::{CursorPos, Size};
+use marauder_core::conf::Config;
+use marauder_core::types::{MInt, Size2};
 use std::cell::RefCell;
 
 pub struct Context {
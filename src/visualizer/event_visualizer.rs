@@ -1,14 +1,14 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::core::{Core, UnitTypeId};
-use crate::core::game_state::GameState;
-use crate::core::types::{MapPos, UnitId};
 use crate::visualizer::geom;
 use crate::visualizer::mesh::MeshId;
 use crate::visualizer::scene::{NodeId, Scene, SceneNode, MIN_MARKER_NODE_ID, SHELL_NODE_ID};
 use crate::visualizer::types::{MFloat, Time, WorldPos};
 use crate::visualizer::unit_type_visual_info::UnitTypeVisualInfo;
 use cgmath::{EuclideanVector, Vector, Vector3};
+use marauder_core::core::{ObjectTypes, UnitTypeId};
+use marauder_core::game_state::GameState;
+use marauder_core::types::{MInt, MapPos, UnitId};
 use rand::Rng;
 
 fn unit_id_to_node_id(unit_id: UnitId) -> NodeId {
@@ -133,8 +133,15 @@ pub struct EventCreateUnitVisualizer {
     move_helper: MoveHelper,
 }
 
-fn get_unit_scene_nodes(core: &Core, type_id: UnitTypeId, mesh_id: MeshId) -> Vec<SceneNode> {
-    let count = core.object_types().get_unit_type(type_id).count;
+// Models keep their places in the full squad's circle,
+// so a depleted squad looks like the full one with gaps
+fn get_unit_scene_nodes(
+    object_types: &ObjectTypes,
+    type_id: UnitTypeId,
+    alive_count: MInt,
+    mesh_id: MeshId,
+) -> Vec<SceneNode> {
+    let count = object_types.get_unit_type(type_id).count;
     let mut vec = Vec::new();
     if count == 1 {
         vec![SceneNode {
@@ -150,7 +157,7 @@ fn get_unit_scene_nodes(core: &Core, type_id: UnitTypeId, mesh_id: MeshId) -> Ve
             children: vec![],
         }]
     } else {
-        for i in 0..count {
+        for i in 0..alive_count {
             let pos = geom::index_to_circle_vertex(count, i).v.mul_s(0.3_f32);
             vec.push(SceneNode {
                 pos: WorldPos { v: pos },
@@ -165,11 +172,12 @@ fn get_unit_scene_nodes(core: &Core, type_id: UnitTypeId, mesh_id: MeshId) -> Ve
 
 impl EventCreateUnitVisualizer {
     pub fn new(
-        core: &Core,
+        object_types: &ObjectTypes,
         scene: &mut Scene,
         _: &GameState,
         id: UnitId,
         type_id: UnitTypeId,
+        count: MInt,
         pos: MapPos,
         mesh_id: MeshId,
         marker_mesh_id: MeshId,
@@ -181,6 +189,7 @@ impl EventCreateUnitVisualizer {
             v: to.v.sub_v(vec3_z(geom::HEX_EX_RADIUS / 2.0)),
         };
 
+        // visual only, core's RNG must not be touched here
         let rot = rand::thread_rng().gen_range::<MFloat, MFloat, MFloat>(0.0, 360.0);
         let _ = scene.nodes.insert(
             node_id,
@@ -188,7 +197,7 @@ impl EventCreateUnitVisualizer {
                 pos: from,
                 rot,
                 mesh_id: None,
-                children: get_unit_scene_nodes(core, type_id, mesh_id),
+                children: get_unit_scene_nodes(object_types, type_id, count, mesh_id),
             },
         );
         let _ = scene.nodes.insert(
@@ -221,6 +230,38 @@ impl EventVisualizer for EventCreateUnitVisualizer {
     fn end(&mut self, _: &mut Scene, _: &GameState) {}
 }
 
+pub struct EventHideUnitVisualizer {
+    id: UnitId,
+    move_helper: MoveHelper,
+}
+
+impl EventHideUnitVisualizer {
+    pub fn new(scene: &mut Scene, _: &GameState, id: UnitId) -> Box<dyn EventVisualizer + 'static> {
+        let from = scene.nodes[&unit_id_to_node_id(id)].pos;
+        let to = WorldPos {
+            v: from.v.sub_v(vec3_z(geom::HEX_EX_RADIUS / 2.0)),
+        };
+        let move_helper = MoveHelper::new(from, to, 1.0);
+        Box::new(EventHideUnitVisualizer { id, move_helper }) as Box<dyn EventVisualizer>
+    }
+}
+
+impl EventVisualizer for EventHideUnitVisualizer {
+    fn is_finished(&self) -> bool {
+        self.move_helper.is_finished()
+    }
+
+    fn draw(&mut self, scene: &mut Scene, dtime: Time) {
+        let node_id = unit_id_to_node_id(self.id);
+        scene.nodes.get_mut(&node_id).unwrap().pos = self.move_helper.step(dtime);
+    }
+
+    fn end(&mut self, scene: &mut Scene, _: &GameState) {
+        let _ = scene.nodes.remove(&unit_id_to_node_id(self.id)).unwrap();
+        let _ = scene.nodes.remove(&marker_id(self.id)).unwrap();
+    }
+}
+
 pub struct MoveHelper {
     to: WorldPos,
     current: WorldPos,
@@ -264,7 +305,8 @@ fn vec3_z(z: MFloat) -> Vector3<MFloat> {
 
 pub struct EventAttackUnitVisualizer {
     defender_id: UnitId,
-    killed: bool,
+    killed_count: MInt,
+    is_destroyed: bool,
     move_helper: MoveHelper,
     shell_move: MoveHelper,
 }
@@ -275,7 +317,8 @@ impl EventAttackUnitVisualizer {
         _: &GameState,
         attacker_id: UnitId,
         defender_id: UnitId,
-        killed: bool,
+        killed_count: MInt,
+        is_destroyed: bool,
         shell_mesh_id: MeshId,
     ) -> Box<dyn EventVisualizer + 'static> {
         let node_id = unit_id_to_node_id(defender_id.clone());
@@ -300,7 +343,8 @@ impl EventAttackUnitVisualizer {
         };
         Box::new(EventAttackUnitVisualizer {
             defender_id: defender_id.clone(),
-            killed,
+            killed_count,
+            is_destroyed,
             move_helper,
             shell_move,
         }) as Box<dyn EventVisualizer>
@@ -309,7 +353,7 @@ impl EventAttackUnitVisualizer {
 
 impl EventVisualizer for EventAttackUnitVisualizer {
     fn is_finished(&self) -> bool {
-        if self.killed {
+        if self.is_destroyed {
             self.move_helper.is_finished()
         } else {
             self.shell_move.is_finished()
@@ -318,17 +362,22 @@ impl EventVisualizer for EventAttackUnitVisualizer {
 
     fn draw(&mut self, scene: &mut Scene, dtime: Time) {
         scene.nodes.get_mut(&SHELL_NODE_ID).unwrap().pos = self.shell_move.step(dtime);
-        if self.killed && self.shell_move.is_finished() {
+        if self.is_destroyed && self.shell_move.is_finished() {
             let node_id = unit_id_to_node_id(self.defender_id.clone());
             scene.nodes.get_mut(&node_id).unwrap().pos = self.move_helper.step(dtime);
         }
     }
 
     fn end(&mut self, scene: &mut Scene, _: &GameState) {
-        if self.killed {
-            let node_id = unit_id_to_node_id(self.defender_id.clone());
+        let node_id = unit_id_to_node_id(self.defender_id.clone());
+        if self.is_destroyed {
             let _ = scene.nodes.remove(&node_id).unwrap();
             let _ = scene.nodes.remove(&marker_id(self.defender_id)).unwrap();
+        } else {
+            // one model for every wound
+            let children = &mut scene.nodes.get_mut(&node_id).unwrap().children;
+            let alive_count = children.len() - self.killed_count as usize;
+            children.truncate(alive_count);
         }
         let _ = scene.nodes.remove(&SHELL_NODE_ID).unwrap();
     }
@@ -1,12 +1,12 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::misc::add_quad_to_vec;
-use crate::core::types::{MInt, Size2};
 use crate::visualizer::mesh::Mesh;
+use crate::visualizer::misc::add_quad_to_vec;
 use crate::visualizer::shader::Shader;
 use crate::visualizer::texture::Texture;
 use crate::visualizer::types::{MFloat, ScreenPos, TextureCoord, VertexCoord};
 use cgmath::{Vector2, Vector3};
+use marauder_core::types::{MInt, Size2};
 use stb_tt::Font;
 
 use std::collections::HashMap;
@@ -0,0 +1,140 @@
+// See LICENSE file for copyright and license details.
+
+use marauder_core::core::Player;
+use marauder_core::types::{MInt, PlayerId};
+use marauder_core::victory::{GameResult, VictoryReason};
+use crate::visualizer::context::Context;
+use crate::visualizer::gui::{Button, ButtonId, ButtonManager};
+use crate::visualizer::mgl;
+use crate::visualizer::state_visualizer::StateChangeCommand::EndGame;
+use crate::visualizer::state_visualizer::{StateChangeCommand, StateVisualizer};
+use crate::visualizer::types::{ScreenPos, Time};
+use cgmath::Vector2;
+use std::ops::DerefMut;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+fn get_player_name(players: &[Player], player_id: &PlayerId) -> String {
+    players[player_id.id as usize].name.clone()
+}
+
+fn get_result_lines(players: &[Player], result: &GameResult) -> Vec<String> {
+    let mut lines = Vec::new();
+    lines.push(match result.winner {
+        Some(ref winner) => format!("{} wins", get_player_name(players, winner)),
+        None => "Draw".to_string(),
+    });
+    lines.push(match result.reason {
+        VictoryReason::EnemiesDestroyed => "all enemy units destroyed".to_string(),
+        VictoryReason::ObjectivesHeld => "objectives held".to_string(),
+        VictoryReason::TurnLimit => "turn limit reached".to_string(),
+    });
+    lines.push(format!("turns: {}", result.turns));
+    for stats in result.stats.iter() {
+        lines.push(format!(
+            "{}: points {}, killed {}, lost {}",
+            get_player_name(players, &stats.player_id),
+            stats.victory_points,
+            stats.units_killed,
+            stats.units_lost,
+        ));
+    }
+    lines
+}
+
+// Shows the winner and statistics of a finished game
+pub struct GameOverStateVisualizer {
+    button_manager: ButtonManager,
+    button_menu_id: ButtonId,
+    commands_rx: Receiver<StateChangeCommand>,
+    commands_tx: Sender<StateChangeCommand>,
+}
+
+impl GameOverStateVisualizer {
+    pub fn new(
+        context: &Context,
+        players: &[Player],
+        result: &GameResult,
+    ) -> GameOverStateVisualizer {
+        let mut button_manager = ButtonManager::new();
+        // text lines are buttons that do nothing
+        for (i, line) in get_result_lines(players, result).iter().enumerate() {
+            let _ = button_manager.add_button(Button::new(
+                line,
+                context.font_stash.borrow_mut().deref_mut(),
+                &context.shader,
+                ScreenPos {
+                    v: Vector2 {
+                        x: 10,
+                        y: context.win_size.h - 40 - i as MInt * 30,
+                    },
+                },
+            ));
+        }
+        let button_menu_id = button_manager.add_button(Button::new(
+            "menu",
+            context.font_stash.borrow_mut().deref_mut(),
+            &context.shader,
+            ScreenPos {
+                v: Vector2 { x: 10, y: 10 },
+            },
+        ));
+        let (commands_tx, commands_rx) = channel();
+        GameOverStateVisualizer {
+            button_manager,
+            button_menu_id,
+            commands_rx,
+            commands_tx,
+        }
+    }
+
+    fn handle_mouse_button_event(&mut self, context: &Context) {
+        match self.button_manager.get_clicked_button_id(context) {
+            Some(button_id) => {
+                if button_id == self.button_menu_id {
+                    self.commands_tx.send(EndGame).unwrap();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+impl StateVisualizer for GameOverStateVisualizer {
+    fn logic(&mut self, _: &Context) {}
+
+    fn draw(&mut self, context: &mut Context, _: Time) {
+        mgl::set_clear_color(mgl::BLACK_3);
+        mgl::clear_screen();
+        context.shader.activate();
+        context
+            .shader
+            .uniform_color(context.basic_color_id, mgl::WHITE);
+        self.button_manager.draw(context);
+        use glfw::Context;
+        context.win.swap_buffers();
+    }
+
+    fn handle_event(&mut self, context: &Context, event: glfw::WindowEvent) {
+        match event {
+            glfw::WindowEvent::Key(key, _, glfw::Action::Press, _) => match key {
+                glfw::Key::Escape | glfw::Key::Q | glfw::Key::Enter => {
+                    self.commands_tx.send(EndGame).unwrap();
+                }
+                _ => {}
+            },
+            glfw::WindowEvent::MouseButton(glfw::MouseButtonLeft, glfw::Action::Press, _) => {
+                self.handle_mouse_button_event(context);
+            }
+            _ => {}
+        }
+    }
+
+    fn get_command(&self) -> Option<StateChangeCommand> {
+        match self.commands_rx.try_recv() {
+            Ok(cmd) => Some(cmd),
+            Err(_) => None,
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -1,42 +1,103 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::core::Command::{
-    CommandAttackUnit, CommandCreateUnit, CommandEndTurn, CommandMove,
-};
-use crate::core::core::Event::{EventAttackUnit, EventCreateUnit, EventEndTurn, EventMove};
-use crate::core::core::{Core, Event, UnitTypeId};
-use crate::core::dir::Dir;
-use crate::core::fs::FileSystem;
-use crate::core::game_state::GameState;
-use crate::core::map::{distance, MapPosIter};
-use crate::core::pathfinder::Pathfinder;
-use crate::core::types::{MInt, MapPos, PlayerId, Size2, UnitId};
 use crate::visualizer::camera::Camera;
 use crate::visualizer::context::Context;
 use crate::visualizer::event_visualizer::{
     EventAttackUnitVisualizer, EventCreateUnitVisualizer, EventEndTurnVisualizer,
-    EventMoveVisualizer, EventVisualizer,
+    EventHideUnitVisualizer, EventMoveVisualizer, EventVisualizer,
 };
 use crate::visualizer::gui::{Button, ButtonId, ButtonManager};
+use crate::visualizer::handover_state_visualizer::get_events_summary;
 use crate::visualizer::mesh::{Mesh, MeshId};
 use crate::visualizer::picker::PickResult::{PickedMapPos, PickedUnitId};
 use crate::visualizer::scene::{Scene, SceneNode};
 use crate::visualizer::selection::{get_selection_mesh, SelectionManager};
 use crate::visualizer::shader::Shader;
-use crate::visualizer::state_visualizer::StateChangeCommand::EndGame;
+use crate::visualizer::state_visualizer::StateChangeCommand::{
+    EndGame, ShowGameOver, ShowHandover,
+};
 use crate::visualizer::state_visualizer::{StateChangeCommand, StateVisualizer};
 use crate::visualizer::texture::Texture;
-use crate::visualizer::types::{MFloat, ScreenPos, TextureCoord, Time, VertexCoord, WorldPos};
+use crate::visualizer::types::{
+    Color3, MFloat, ScreenPos, TextureCoord, Time, VertexCoord, WorldPos,
+};
 use crate::visualizer::unit_type_visual_info::{UnitTypeVisualInfo, UnitTypeVisualInfoManager};
 use crate::visualizer::{geom, mgl, obj, picker};
 use cgmath::{Matrix4, Vector2, Vector3};
-use std::collections::HashMap;
+use marauder_core::ai::Ai;
+use marauder_core::combat::{get_best_weapon_index, predict};
+use marauder_core::core::Command::{
+    CommandAttackUnit, CommandCreateUnit, CommandEndTurn, CommandMove, CommandOverwatch,
+};
+use marauder_core::core::Event::{
+    EventAttackUnit, EventCreateUnit, EventEndTurn, EventGameOver, EventHideUnit, EventMove,
+    EventOverwatch, EventShowUnit,
+};
+use marauder_core::core::{
+    Command, Event, ObjectTypes, Player, TerrainType, TerrainTypeId, Unit, UnitClass, UnitType,
+    UnitTypeId, WeaponType,
+};
+use marauder_core::dir::Dir;
+use marauder_core::fs::FileSystem;
+use marauder_core::game::Game;
+use marauder_core::game_state::GameState;
+use marauder_core::los::check_line_of_fire;
+use marauder_core::map::{MapPosIter, TerrainMap};
+use marauder_core::pathfinder::Pathfinder;
+use marauder_core::types::{MInt, MapPos, PlayerId, Size2, UnitId};
+use marauder_core::victory::GameResult;
+use std::collections::{HashMap, VecDeque};
 use std::ops::DerefMut;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use time::precise_time_ns;
 
-fn get_marker(shader: &Shader, tex_path: &Path) -> Mesh {
+const PLAYER_COLORS: [Color3; 4] = [
+    Color3 {
+        r: 0.2,
+        g: 0.3,
+        b: 0.9,
+    },
+    Color3 {
+        r: 0.9,
+        g: 0.2,
+        b: 0.2,
+    },
+    Color3 {
+        r: 0.2,
+        g: 0.8,
+        b: 0.2,
+    },
+    Color3 {
+        r: 0.9,
+        g: 0.8,
+        b: 0.1,
+    },
+];
+
+// Players beyond the palette get evenly spread hues
+fn get_player_color(player_id: &PlayerId) -> Color3 {
+    if let Some(color) = PLAYER_COLORS.get(player_id.id as usize) {
+        return *color;
+    }
+    let hue = (player_id.id as MFloat * 0.618_034) % 1.0 * 6.0;
+    let (s, v) = (0.8, 0.9);
+    let f = hue - hue.floor();
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * f);
+    let t = v * (1.0 - s * (1.0 - f));
+    let (r, g, b) = match hue as MInt {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    Color3 { r, g, b }
+}
+
+fn get_marker(shader: &Shader, tex: Texture) -> Mesh {
     let n = 0.2;
     let vertex_data = vec![
         VertexCoord {
@@ -73,7 +134,6 @@ fn get_marker(shader: &Shader, tex_path: &Path) -> Mesh {
         },
     ];
     let mut mesh = Mesh::new(vertex_data.as_slice());
-    let tex = Texture::new(tex_path);
     mesh.set_texture(tex, tex_data.as_slice());
     mesh.prepare(shader);
     mesh
@@ -87,10 +147,10 @@ fn get_scenes(players_count: MInt) -> HashMap<PlayerId, Scene> {
     m
 }
 
-fn get_game_states(players_count: MInt) -> HashMap<PlayerId, GameState> {
+fn get_game_states(players_count: MInt, map: &TerrainMap) -> HashMap<PlayerId, GameState> {
     let mut m = HashMap::new();
     for i in 0..players_count {
-        let _ = m.insert(PlayerId { id: i }, GameState::new());
+        let _ = m.insert(PlayerId { id: i }, GameState::new(map.clone()));
     }
     m
 }
@@ -103,11 +163,25 @@ fn get_pathfinders(players_count: MInt, map_size: Size2<MInt>) -> HashMap<Player
     m
 }
 
-fn build_walkable_mesh(pathfinder: &Pathfinder, shader: &Shader) -> Mesh {
+fn get_ais(players: &[Player], map_size: Size2<MInt>) -> HashMap<PlayerId, Ai> {
+    let mut m = HashMap::new();
+    for player in players.iter() {
+        if player.is_ai {
+            let _ = m.insert(player.id.clone(), Ai::new(player.id.clone(), map_size));
+        }
+    }
+    m
+}
+
+// Lines to reachable tiles where the unit has to stop (or can go on)
+fn build_walkable_mesh(pathfinder: &Pathfinder, is_stop: bool, shader: &Shader) -> Mesh {
     let map = pathfinder.get_map();
     let map_size = map.get_size();
     let mut vertex_data = Vec::new();
     for tile_pos in MapPosIter::new(map_size) {
+        if map.tile(tile_pos).is_stop != is_stop {
+            continue;
+        }
         match map.tile(tile_pos.clone()).parent {
             Some(parent_dir) => {
                 let tile_pos_to = Dir::get_neighbour_pos(tile_pos, parent_dir);
@@ -129,10 +203,96 @@ fn build_walkable_mesh(pathfinder: &Pathfinder, shader: &Shader) -> Mesh {
     mesh
 }
 
-fn get_map_mesh(fs: &FileSystem, map_size: Size2<MInt>, shader: &Shader) -> Mesh {
+// Weapon that will fire: the chosen one or the best one in line of fire
+fn get_weapon_index(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    attacker: &Unit,
+    defender: &Unit,
+    chosen_weapon_index: Option<MInt>,
+) -> Option<MInt> {
+    match chosen_weapon_index {
+        Some(weapon_index) => Some(weapon_index),
+        None => get_best_weapon_index(object_types, state, attacker, defender),
+    }
+}
+
+// Lines from the attacker to enemies that it can (or can not) fire at
+fn build_targets_mesh(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    attacker: &Unit,
+    chosen_weapon_index: Option<MInt>,
+    can_attack: bool,
+    shader: &Shader,
+) -> Mesh {
+    let mut vertex_data = Vec::new();
+    let world_pos_from = geom::map_pos_to_world_pos(attacker.pos);
+    for (_, unit) in state.units.iter() {
+        if unit.player_id == attacker.player_id {
+            continue;
+        }
+        let is_ok =
+            match get_weapon_index(object_types, state, attacker, unit, chosen_weapon_index) {
+                Some(weapon_index) => {
+                    check_line_of_fire(object_types, state, attacker, weapon_index, unit.pos)
+                        .is_ok()
+                }
+                None => false,
+            };
+        if is_ok != can_attack {
+            continue;
+        }
+        let world_pos_to = geom::map_pos_to_world_pos(unit.pos);
+        vertex_data.push(VertexCoord {
+            v: geom::lift(world_pos_from.v),
+        });
+        vertex_data.push(VertexCoord {
+            v: geom::lift(world_pos_to.v),
+        });
+    }
+    let mut mesh = Mesh::new(vertex_data.as_slice());
+    mesh.set_mode(mgl::MeshRenderMode::Lines);
+    mesh.prepare(shader);
+    mesh
+}
+
+fn get_terrain_texture(fs: &FileSystem, terrain_type: &TerrainType) -> Texture {
+    let path = fs.get(&Path::new(&format!(
+        "data/terrain_{}.png",
+        terrain_type.name
+    )));
+    if path.exists() {
+        Texture::new(&path)
+    } else {
+        Texture::new(&fs.get(&Path::new("data/floor.png")))
+    }
+}
+
+fn get_shell_texture(fs: &FileSystem, weapon_type: &WeaponType) -> Texture {
+    let name = weapon_type.name.replace(' ', "_");
+    let path = fs.get(Path::new(&format!("data/shell_{}.png", name)));
+    if path.exists() {
+        Texture::new(&path)
+    } else {
+        Texture::new(&fs.get(Path::new("data/shell.png")))
+    }
+}
+
+// One mesh per terrain type because mesh can have only one texture
+fn get_map_mesh(
+    fs: &FileSystem,
+    object_types: &ObjectTypes,
+    map: &TerrainMap,
+    terrain_type_id: TerrainTypeId,
+    shader: &Shader,
+) -> Mesh {
     let mut vertex_data = Vec::new();
     let mut tex_data = Vec::new();
-    for tile_pos in MapPosIter::new(map_size) {
+    for tile_pos in MapPosIter::new(map.get_size()) {
+        if map.terrain_type_id(tile_pos) != terrain_type_id {
+            continue;
+        }
         let pos = geom::map_pos_to_world_pos(tile_pos);
         for num in 0..6 {
             let vertex = geom::index_to_hex_vertex(num);
@@ -155,13 +315,30 @@ fn get_map_mesh(fs: &FileSystem, map_size: Size2<MInt>, shader: &Shader) -> Mesh
             });
         }
     }
-    let tex = Texture::new(&fs.get(&Path::new("data/floor.png")));
+    let terrain_type = object_types.get_terrain_type(terrain_type_id);
+    let tex = get_terrain_texture(fs, terrain_type);
     let mut mesh = Mesh::new(vertex_data.as_slice());
     mesh.set_texture(tex, tex_data.as_slice());
     mesh.prepare(shader);
     mesh
 }
 
+fn get_map_mesh_ids(
+    fs: &FileSystem,
+    object_types: &ObjectTypes,
+    map: &TerrainMap,
+    shader: &Shader,
+    meshes: &mut Vec<Mesh>,
+) -> Vec<MeshId> {
+    let mut mesh_ids = Vec::new();
+    for id in 0..object_types.terrain_types().len() {
+        let terrain_type_id = TerrainTypeId { id: id as MInt };
+        let mesh = get_map_mesh(fs, object_types, map, terrain_type_id, shader);
+        mesh_ids.push(add_mesh(meshes, mesh));
+    }
+    mesh_ids
+}
+
 fn load_unit_mesh(fs: &FileSystem, shader: &Shader, name: &str) -> Mesh {
     let png = format!("data/{}.png", name);
     let obj = format!("data/{}.obj", name);
@@ -175,14 +352,14 @@ fn load_unit_mesh(fs: &FileSystem, shader: &Shader, name: &str) -> Mesh {
     mesh
 }
 
-fn add_mesh(meshes: &mut Vec<Mesh>, mesh: Mesh) -> MeshId {
+pub fn add_mesh(meshes: &mut Vec<Mesh>, mesh: Mesh) -> MeshId {
     meshes.push(mesh);
     MeshId {
         id: (meshes.len() as MInt) - 1,
     }
 }
 
-fn get_initial_camera_pos(map_size: &Size2<MInt>) -> WorldPos {
+pub fn get_initial_camera_pos(map_size: &Size2<MInt>) -> WorldPos {
     let pos = get_max_camera_pos(map_size);
     WorldPos {
         v: Vector3 {
@@ -193,7 +370,7 @@ fn get_initial_camera_pos(map_size: &Size2<MInt>) -> WorldPos {
     }
 }
 
-fn get_max_camera_pos(map_size: &Size2<MInt>) -> WorldPos {
+pub fn get_max_camera_pos(map_size: &Size2<MInt>) -> WorldPos {
     let pos = geom::map_pos_to_world_pos(MapPos {
         v: Vector2 {
             x: map_size.w,
@@ -210,13 +387,26 @@ fn get_max_camera_pos(map_size: &Size2<MInt>) -> WorldPos {
 }
 
 fn get_marker_mesh_id(mesh_ids: &MeshIdManager, player_id: PlayerId) -> MeshId {
-    match player_id.id {
-        0 => mesh_ids.marker_1_mesh_id,
-        1 => mesh_ids.marker_2_mesh_id,
-        n => panic!("Wrong player id: {}", n),
+    match mesh_ids.marker_mesh_ids.get(player_id.id as usize) {
+        Some(mesh_id) => *mesh_id,
+        None => panic!("Wrong player id: {}", player_id.id),
     }
 }
 
+// Attacker is visible to the player when its attack is shown
+fn get_shell_mesh_id(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    mesh_ids: &MeshIdManager,
+    attacker_id: UnitId,
+    weapon_index: MInt,
+) -> MeshId {
+    let type_id = state.units[&attacker_id].type_id;
+    let unit_type = object_types.get_unit_type(type_id);
+    let weapon_type_id = unit_type.weapon_type_ids[weapon_index as usize];
+    mesh_ids.shell_mesh_ids[weapon_type_id.id as usize]
+}
+
 fn get_unit_mesh_id(
     unit_type_visual_info: &UnitTypeVisualInfoManager,
     unit_type_id: UnitTypeId,
@@ -224,11 +414,123 @@ fn get_unit_mesh_id(
     unit_type_visual_info.get(unit_type_id).mesh_id
 }
 
-struct MeshIdManager {
-    map_mesh_id: MeshId,
-    shell_mesh_id: MeshId,
-    marker_1_mesh_id: MeshId,
-    marker_2_mesh_id: MeshId,
+pub struct MeshIdManager {
+    pub map_mesh_ids: Vec<MeshId>,
+    // one per weapon type
+    pub shell_mesh_ids: Vec<MeshId>,
+    // one per player
+    pub marker_mesh_ids: Vec<MeshId>,
+}
+
+// Map, shell and markers meshes
+pub fn get_mesh_ids(
+    fs: &FileSystem,
+    shader: &Shader,
+    object_types: &ObjectTypes,
+    map: &TerrainMap,
+    players_count: MInt,
+    meshes: &mut Vec<Mesh>,
+) -> MeshIdManager {
+    let map_mesh_ids = get_map_mesh_ids(fs, object_types, map, shader, meshes);
+    let mut shell_mesh_ids = Vec::new();
+    for weapon_type in object_types.weapon_types().iter() {
+        let shell_tex = get_shell_texture(fs, weapon_type);
+        shell_mesh_ids.push(add_mesh(meshes, get_marker(shader, shell_tex)));
+    }
+    let mut marker_mesh_ids = Vec::new();
+    for id in 0..players_count {
+        let color = get_player_color(&PlayerId { id });
+        let mesh = get_marker(shader, Texture::new_color(color));
+        marker_mesh_ids.push(add_mesh(meshes, mesh));
+    }
+    MeshIdManager {
+        map_mesh_ids,
+        shell_mesh_ids,
+        marker_mesh_ids,
+    }
+}
+
+pub fn make_event_visualizer(
+    object_types: &ObjectTypes,
+    scene: &mut Scene,
+    state: &GameState,
+    unit_type_visual_info: &UnitTypeVisualInfoManager,
+    mesh_ids: &MeshIdManager,
+    event: &Event,
+) -> Box<dyn EventVisualizer + 'static> {
+    match *event {
+        EventMove(unit_id, ref path) => {
+            let type_id = state.units[&unit_id].type_id;
+            let unit_type_visual_info = unit_type_visual_info.get(type_id);
+            EventMoveVisualizer::new(scene, state, unit_id, unit_type_visual_info, path.clone())
+        }
+        EventEndTurn(_, _) | EventGameOver(_) | EventOverwatch(_) => {
+            EventEndTurnVisualizer::new()
+        }
+        EventCreateUnit(id, ref pos, ref type_id, ref player_id) => EventCreateUnitVisualizer::new(
+            object_types,
+            scene,
+            state,
+            id,
+            *type_id,
+            object_types.get_unit_type(*type_id).count,
+            *pos,
+            get_unit_mesh_id(unit_type_visual_info, type_id.clone()),
+            get_marker_mesh_id(mesh_ids, player_id.clone()),
+        ),
+        EventAttackUnit(attacker_id, defender_id, ref result) => EventAttackUnitVisualizer::new(
+            scene,
+            state,
+            attacker_id,
+            defender_id,
+            result.killed_count(),
+            result.is_destroyed(&state.units[&defender_id]),
+            get_shell_mesh_id(
+                object_types,
+                state,
+                mesh_ids,
+                attacker_id,
+                result.weapon_index,
+            ),
+        ),
+        EventShowUnit(ref unit) => EventCreateUnitVisualizer::new(
+            object_types,
+            scene,
+            state,
+            unit.id,
+            unit.type_id,
+            unit.count,
+            unit.pos,
+            get_unit_mesh_id(unit_type_visual_info, unit.type_id),
+            get_marker_mesh_id(mesh_ids, unit.player_id.clone()),
+        ),
+        EventHideUnit(id) => EventHideUnitVisualizer::new(scene, state, id),
+    }
+}
+
+pub fn draw_scene_node(meshes: &[Mesh], node: &SceneNode, m: Matrix4<MFloat>, context: &Context) {
+    let m = mgl::tr(m, node.pos.v);
+    let m = mgl::rot_z(m, node.rot);
+    match node.mesh_id {
+        Some(mesh_id) => {
+            context.shader.uniform_mat4f(context.mvp_mat_id.clone(), &m);
+            let id = mesh_id.id as usize;
+            meshes[id].draw(&context.shader);
+        }
+        None => {}
+    }
+    for node in node.children.iter() {
+        draw_scene_node(meshes, node, m, context);
+    }
+}
+
+pub fn draw_map(meshes: &[Mesh], mesh_ids: &MeshIdManager, camera: &Camera, context: &Context) {
+    context
+        .shader
+        .uniform_mat4f(context.mvp_mat_id.clone(), &camera.mat());
+    for mesh_id in mesh_ids.map_mesh_ids.iter() {
+        meshes[mesh_id.id as usize].draw(&context.shader);
+    }
 }
 
 pub struct GameStateVisualizer {
@@ -236,6 +538,11 @@ pub struct GameStateVisualizer {
     unit_type_visual_info: UnitTypeVisualInfoManager,
     meshes: Vec<Mesh>,
     walkable_mesh: Option<Mesh>,
+    // reachable tiles in enemies' zones of control
+    stop_walkable_mesh: Option<Mesh>,
+    // enemies that selected unit can hit and ones out of range or sight
+    targets_mesh: Option<Mesh>,
+    blocked_targets_mesh: Option<Mesh>,
     // TODO: move to 'meshes'
     map_text_mesh: Mesh,
     camera: Camera,
@@ -244,63 +551,86 @@ pub struct GameStateVisualizer {
     picker: picker::TilePicker,
     map_pos_under_cursor: Option<MapPos>,
     selected_unit_id: Option<UnitId>,
+    // set by number keys, None lets the core pick the best weapon
+    selected_weapon_index: Option<MInt>,
     unit_under_cursor_id: Option<UnitId>,
     scenes: HashMap<PlayerId, Scene>,
-    core: Core,
+    game: Box<dyn Game>,
     event: Option<Event>,
+    // events taken from the game for handover summary, but not shown yet
+    pending_events: VecDeque<Event>,
     event_visualizer: Option<Box<dyn EventVisualizer + 'static>>,
     game_states: HashMap<PlayerId, GameState>,
     pathfinders: HashMap<PlayerId, Pathfinder>,
+    // computer players make their moves through the same animated events
+    ais: HashMap<PlayerId, Ai>,
+    // several humans play on one screen and must not see each other's scenes
+    is_hotseat: bool,
+    // board is hidden until its player is shown
+    shown_player_id: Option<PlayerId>,
+    is_handover_shown: bool,
     button_manager: ButtonManager,
     button_end_turn_id: ButtonId,
     button_quit_id: ButtonId,
+    // kill chance of selected unit against the enemy under cursor
+    hint_text: Option<String>,
+    hint_button_manager: ButtonManager,
+    // outcome of the last shown attack, e.g. "ARMOR DEFLECTED", or order
+    attack_result_button_manager: ButtonManager,
     selection_manager: SelectionManager,
+    save_path: PathBuf,
+    replay_path: PathBuf,
 }
 
-fn get_unit_type_visual_info(
+fn get_unit_move_speed(unit_type: &UnitType) -> MFloat {
+    match unit_type.class {
+        UnitClass::Infantry => 2.0,
+        UnitClass::Vehicle => 3.8,
+    }
+}
+
+pub fn get_unit_type_visual_info(
     fs: &FileSystem,
     context: &Context,
+    object_types: &ObjectTypes,
     meshes: &mut Vec<Mesh>,
 ) -> UnitTypeVisualInfoManager {
-    let tank_mesh_id = add_mesh(meshes, load_unit_mesh(fs, &context.shader, "tank"));
-    let soldier_mesh_id = add_mesh(meshes, load_unit_mesh(fs, &context.shader, "soldier"));
     let mut unit_type_visual_info = UnitTypeVisualInfoManager::new();
-    // TODO: Add by name not by order
-    unit_type_visual_info.add_info(UnitTypeVisualInfo {
-        mesh_id: tank_mesh_id,
-        move_speed: 3.8,
-    });
-    unit_type_visual_info.add_info(UnitTypeVisualInfo {
-        mesh_id: soldier_mesh_id,
-        move_speed: 2.0,
-    });
+    // unit types are stored by id, so infos must be added in the same order
+    for unit_type in object_types.unit_types().iter() {
+        let mesh = load_unit_mesh(fs, &context.shader, &unit_type.name);
+        unit_type_visual_info.add_info(UnitTypeVisualInfo {
+            mesh_id: add_mesh(meshes, mesh),
+            move_speed: get_unit_move_speed(unit_type),
+        });
+    }
     unit_type_visual_info
 }
 
 impl GameStateVisualizer {
-    pub fn new(fs: &FileSystem, context: &Context) -> GameStateVisualizer {
+    pub fn new(
+        fs: &FileSystem,
+        context: &Context,
+        game: Box<dyn Game>,
+        save_path: &Path,
+        replay_path: &Path,
+    ) -> GameStateVisualizer {
         // set_error_context!("constructing GameStateVisualizer", "-");
-        let players_count = 2;
-        let core = Core::new(fs);
-        let map_size = core.map_size();
-        let game_states = get_game_states(players_count);
-        let picker = picker::TilePicker::new(fs, &game_states[&core.player_id()], core.map_size());
+        let players_count = game.players().len() as MInt;
+        let map_size = game.map_size();
+        let game_states = get_game_states(players_count, game.map());
+        let picker = picker::TilePicker::new(fs, &game_states[&game.player_id()], game.map_size());
         let mut meshes = Vec::new();
-        let map_mesh_id = add_mesh(&mut meshes, get_map_mesh(fs, map_size, &context.shader));
-        let selection_marker_mesh_id =
-            add_mesh(&mut meshes, get_selection_mesh(fs, &context.shader));
-        let shell_mesh_id = add_mesh(
-            &mut meshes,
-            get_marker(&context.shader, &fs.get(&Path::new("data/shell.png"))),
-        );
-        let marker_1_mesh_id = add_mesh(
-            &mut meshes,
-            get_marker(&context.shader, &fs.get(&Path::new("data/flag1.png"))),
-        );
-        let marker_2_mesh_id = add_mesh(
+        let mesh_ids = get_mesh_ids(
+            fs,
+            &context.shader,
+            game.object_types(),
+            game.map(),
+            players_count,
             &mut meshes,
-            get_marker(&context.shader, &fs.get(&Path::new("data/flag2.png"))),
         );
+        let selection_marker_mesh_id =
+            add_mesh(&mut meshes, get_selection_mesh(fs, &context.shader));
         let mut camera = Camera::new(context.win_size);
         camera.set_max_pos(get_max_camera_pos(&map_size));
         camera.set_pos(get_initial_camera_pos(&map_size));
@@ -326,17 +656,25 @@ impl GameStateVisualizer {
             .borrow_mut()
             .deref_mut()
             .get_mesh("test text", &context.shader);
-        // TODO: store this info in separate json
-        let mesh_ids = MeshIdManager {
-            map_mesh_id,
-            shell_mesh_id,
-            marker_1_mesh_id,
-            marker_2_mesh_id,
+        // server plays computer players of network game
+        let ais = match game.core() {
+            Some(_) => get_ais(game.players(), map_size),
+            None => HashMap::new(),
         };
+        let is_hotseat = game.core().is_some()
+            && game.players().iter().filter(|player| !player.is_ai).count() > 1;
         let (commands_tx, commands_rx) = channel();
         let vis = GameStateVisualizer {
             walkable_mesh: None,
-            unit_type_visual_info: get_unit_type_visual_info(fs, context, &mut meshes),
+            stop_walkable_mesh: None,
+            targets_mesh: None,
+            blocked_targets_mesh: None,
+            unit_type_visual_info: get_unit_type_visual_info(
+                fs,
+                context,
+                game.object_types(),
+                &mut meshes,
+            ),
             mesh_ids,
             meshes,
             map_text_mesh,
@@ -344,56 +682,55 @@ impl GameStateVisualizer {
             picker,
             map_pos_under_cursor: None,
             selected_unit_id: None,
+            selected_weapon_index: None,
             unit_under_cursor_id: None,
-            core,
+            game,
             event_visualizer: None,
             event: None,
+            pending_events: VecDeque::new(),
             scenes: get_scenes(players_count),
             game_states,
             pathfinders: get_pathfinders(players_count, map_size),
+            ais,
+            is_hotseat,
+            shown_player_id: None,
+            is_handover_shown: false,
             button_manager,
             button_end_turn_id,
             button_quit_id,
+            hint_text: None,
+            hint_button_manager: ButtonManager::new(),
+            attack_result_button_manager: ButtonManager::new(),
             selection_manager: SelectionManager::new(selection_marker_mesh_id),
+            save_path: save_path.to_path_buf(),
+            replay_path: replay_path.to_path_buf(),
             commands_rx,
             commands_tx,
         };
         vis
     }
 
-    fn scene(&self) -> &Scene {
-        &self.scenes[&self.core.player_id()]
-    }
-
-    fn draw_scene_node(&self, node: &SceneNode, m: Matrix4<MFloat>, context: &Context) {
-        let m = mgl::tr(m, node.pos.v);
-        let m = mgl::rot_z(m, node.rot);
-        match node.mesh_id {
-            Some(mesh_id) => {
-                context.shader.uniform_mat4f(context.mvp_mat_id.clone(), &m);
-                let id = mesh_id.id as usize;
-                self.meshes[id].draw(&context.shader);
+    // Player whose board is on the screen: humans keep watching
+    // their own board while computer players make their moves
+    fn viewer_id(&self) -> PlayerId {
+        if self.is_ai_turn() {
+            if let Some(ref player_id) = self.shown_player_id {
+                return player_id.clone();
             }
-            None => {}
-        }
-        for node in node.children.iter() {
-            self.draw_scene_node(node, m, context);
         }
+        self.game.player_id()
+    }
+
+    fn scene(&self) -> &Scene {
+        &self.scenes[&self.viewer_id()]
     }
 
     fn draw_scene_nodes(&self, context: &Context) {
         for (_, node) in self.scene().nodes.iter() {
-            self.draw_scene_node(node, self.camera.mat(), context);
+            draw_scene_node(&self.meshes, node, self.camera.mat(), context);
         }
     }
 
-    fn draw_map(&mut self, context: &Context) {
-        context
-            .shader
-            .uniform_mat4f(context.mvp_mat_id.clone(), &self.camera.mat());
-        self.meshes[self.mesh_ids.map_mesh_id.id as usize].draw(&context.shader);
-    }
-
     fn draw_3d_text(&mut self, context: &Context) {
         let font_stash = context.font_stash.borrow_mut();
         let m = self.camera.mat();
@@ -408,7 +745,7 @@ impl GameStateVisualizer {
             .shader
             .uniform_color(context.basic_color_id.clone(), mgl::WHITE);
         self.draw_scene_nodes(context);
-        self.draw_map(context);
+        draw_map(&self.meshes, &self.mesh_ids, &self.camera, context);
         match self.walkable_mesh {
             Some(ref walkable_mesh) => {
                 context
@@ -418,36 +755,195 @@ impl GameStateVisualizer {
             }
             None => {}
         }
+        match self.stop_walkable_mesh {
+            Some(ref stop_walkable_mesh) => {
+                context
+                    .shader
+                    .uniform_color(context.basic_color_id.clone(), mgl::YELLOW);
+                stop_walkable_mesh.draw(&context.shader);
+            }
+            None => {}
+        }
+        match self.targets_mesh {
+            Some(ref targets_mesh) => {
+                context
+                    .shader
+                    .uniform_color(context.basic_color_id.clone(), mgl::RED);
+                targets_mesh.draw(&context.shader);
+            }
+            None => {}
+        }
+        match self.blocked_targets_mesh {
+            Some(ref blocked_targets_mesh) => {
+                context
+                    .shader
+                    .uniform_color(context.basic_color_id.clone(), mgl::BLACK);
+                blocked_targets_mesh.draw(&context.shader);
+            }
+            None => {}
+        }
+        let viewer_id = self.viewer_id();
         match self.event_visualizer {
             Some(ref mut event_visualizer) => {
-                let scene = self.scenes.get_mut(&self.core.player_id()).unwrap();
+                let scene = self.scenes.get_mut(&viewer_id).unwrap();
                 event_visualizer.draw(scene, dtime);
             }
             None => {}
         }
     }
 
+    fn save_replay(&self) {
+        match self.game.core() {
+            Some(core) => {
+                core.replay().save(&self.replay_path);
+                println!("Replay saved to {}", self.replay_path.display());
+            }
+            None => {}
+        }
+    }
+
+    fn end_game(&mut self) {
+        self.save_replay();
+        self.commands_tx.send(EndGame).unwrap();
+    }
+
+    fn show_game_over(&mut self, result: GameResult) {
+        self.save_replay();
+        let players = self.game.players().clone();
+        self.commands_tx
+            .send(ShowGameOver(players, result))
+            .unwrap();
+    }
+
+    fn do_command(&mut self, command: Command) -> bool {
+        match self.game.do_command(command) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("Command rejected: {}", err);
+                false
+            }
+        }
+    }
+
+    fn is_ai_turn(&self) -> bool {
+        self.ais.contains_key(&self.game.player_id())
+    }
+
+    // Network player waits for other players' turns
+    fn is_input_blocked(&self) -> bool {
+        self.event_visualizer.is_some()
+            || !self.pending_events.is_empty()
+            || self.is_ai_turn()
+            || self.game.current_player_id() != self.game.player_id()
+    }
+
+    fn get_event(&mut self) -> Option<Event> {
+        match self.pending_events.pop_front() {
+            Some(event) => Some(event),
+            None => self.game.get_event(),
+        }
+    }
+
+    // Returns true if the board must stay hidden till the handover is closed
+    fn start_handover(&mut self) -> bool {
+        let player_id = self.game.player_id();
+        if self.shown_player_id.as_ref() == Some(&player_id) {
+            return false;
+        }
+        if self.is_ai_turn() {
+            // board of the first human is shown if the game starts with a computer turn
+            if self.shown_player_id.is_none() {
+                let human = self.game.players().iter().find(|player| !player.is_ai);
+                self.shown_player_id = Some(human.map_or(player_id, |player| player.id.clone()));
+            }
+            return false;
+        }
+        if self.is_handover_shown || !self.is_hotseat {
+            self.is_handover_shown = false;
+            self.shown_player_id = Some(player_id);
+            return false;
+        }
+        while let Some(event) = self.game.get_event() {
+            self.pending_events.push_back(event);
+        }
+        let events: Vec<Event> = self.pending_events.iter().cloned().collect();
+        let summary = get_events_summary(
+            self.game.object_types(),
+            &self.game_states[&player_id],
+            &player_id,
+            &events,
+        );
+        let player_name = self.game.players()[player_id.id as usize].name.clone();
+        self.commands_tx
+            .send(ShowHandover(player_name, summary))
+            .unwrap();
+        self.is_handover_shown = true;
+        true
+    }
+
+    fn do_ai_command(&mut self) {
+        let player_id = self.game.player_id();
+        let command = match self.ais.get_mut(&player_id) {
+            Some(ai) => ai.get_command(self.game.object_types(), &self.game_states[&player_id]),
+            None => return,
+        };
+        match command {
+            CommandEndTurn => self.end_turn(),
+            command => {
+                // AI sees less than core, so give up the turn instead of looping
+                if !self.do_command(command) {
+                    self.end_turn();
+                }
+            }
+        }
+    }
+
     fn end_turn(&mut self) {
-        self.core.do_command(CommandEndTurn);
+        let player_id = self.game.player_id();
+        let _ = self.do_command(CommandEndTurn);
         self.selected_unit_id = None;
-        let scene = self.scenes.get_mut(&self.core.player_id());
+        let scene = self.scenes.get_mut(&player_id);
         self.selection_manager.deselect(scene.unwrap());
         self.walkable_mesh = None;
+        self.stop_walkable_mesh = None;
+        self.targets_mesh = None;
+        self.blocked_targets_mesh = None;
     }
 
-    fn is_tile_occupied(&self, pos: MapPos) -> bool {
-        let state = &self.game_states[&self.core.player_id()];
-        state.units_at(pos).len() > 0
+    // Uses player's view, so unseen units do not block the lines here
+    fn update_targets_meshes(&mut self, shader: &Shader) {
+        let state = &self.game_states[&self.game.player_id()];
+        let attacker = match self.selected_unit_id.and_then(|id| state.units.get(&id)) {
+            Some(attacker) => attacker,
+            None => {
+                self.targets_mesh = None;
+                self.blocked_targets_mesh = None;
+                return;
+            }
+        };
+        let object_types = self.game.object_types();
+        self.targets_mesh = Some(build_targets_mesh(
+            object_types,
+            state,
+            attacker,
+            self.selected_weapon_index,
+            true,
+            shader,
+        ));
+        self.blocked_targets_mesh = Some(build_targets_mesh(
+            object_types,
+            state,
+            attacker,
+            self.selected_weapon_index,
+            false,
+            shader,
+        ));
     }
 
     fn create_unit(&mut self) {
         match self.map_pos_under_cursor {
             Some(pos) => {
-                if self.is_tile_occupied(pos) {
-                    return;
-                }
-                let cmd = CommandCreateUnit(pos);
-                self.core.do_command(cmd);
+                let _ = self.do_command(CommandCreateUnit(pos));
             }
             None => {}
         }
@@ -456,61 +952,89 @@ impl GameStateVisualizer {
     fn attack_unit(&mut self) {
         match (self.unit_under_cursor_id, self.selected_unit_id) {
             (Some(defender_id), Some(attacker_id)) => {
-                let state = &self.game_states[&self.core.player_id()];
-                let attacker = &state.units[&attacker_id];
-                if attacker.attacked {
-                    return;
-                }
-                let defender = &state.units[&defender_id];
-                let max_distance = {
-                    let attacker_type = self.core.object_types().get_unit_type(attacker.type_id);
-                    let weapon_type = self.core.get_weapon_type(attacker_type.weapon_type_id);
-                    weapon_type.max_distance
-                };
-                if distance(attacker.pos, defender.pos) > max_distance {
-                    return;
-                }
-                let cmd = CommandAttackUnit(attacker_id, defender_id);
-                self.core.do_command(cmd);
+                let command =
+                    CommandAttackUnit(attacker_id, defender_id, self.selected_weapon_index);
+                let _ = self.do_command(command);
             }
             _ => {}
         }
     }
 
+    fn overwatch(&mut self) {
+        match self.selected_unit_id {
+            Some(unit_id) => {
+                let _ = self.do_command(CommandOverwatch(unit_id));
+            }
+            None => {}
+        }
+    }
+
     fn select_unit(&mut self, context: &Context) {
         match self.unit_under_cursor_id {
             Some(unit_id) => {
                 self.selected_unit_id = Some(unit_id);
-                let state = &self.game_states[&self.core.player_id()];
-                let pf = self.pathfinders.get_mut(&self.core.player_id()).unwrap();
-                pf.fill_map(state, &state.units[&unit_id]);
-                self.walkable_mesh = Some(build_walkable_mesh(pf, &context.shader));
-                let scene = self.scenes.get_mut(&self.core.player_id()).unwrap();
+                self.selected_weapon_index = None;
+                let state = &self.game_states[&self.game.player_id()];
+                let pf = self.pathfinders.get_mut(&self.game.player_id()).unwrap();
+                pf.fill_map(self.game.object_types(), state, &state.units[&unit_id]);
+                self.walkable_mesh = Some(build_walkable_mesh(pf, false, &context.shader));
+                self.stop_walkable_mesh = Some(build_walkable_mesh(pf, true, &context.shader));
+                let scene = self.scenes.get_mut(&self.game.player_id()).unwrap();
                 self.selection_manager
                     .create_selection_marker(state, scene, unit_id);
-                // TODO: highlight potential targets
+                self.update_targets_meshes(&context.shader);
             }
             None => {}
         }
     }
 
-    fn handle_key_event(&mut self, _: &Context, key: glfw::Key) {
+    // Number keys pick a weapon of the selected unit, 0 goes back to the best one
+    fn select_weapon(&mut self, context: &Context, number: MInt) {
+        let type_id = match self.selected_unit_id {
+            Some(unit_id) => self.game_states[&self.game.player_id()].units[&unit_id].type_id,
+            None => return,
+        };
+        let unit_type = self.game.object_types().get_unit_type(type_id);
+        if number > unit_type.weapon_type_ids.len() as MInt {
+            return;
+        }
+        self.selected_weapon_index = if number == 0 {
+            None
+        } else {
+            Some(number - 1)
+        };
+        self.update_targets_meshes(&context.shader);
+    }
+
+    fn handle_key_event(&mut self, context: &Context, key: glfw::Key) {
         match key {
-            glfw::Key::Escape | glfw::Key::Q => self.commands_tx.send(EndGame).unwrap(),
+            glfw::Key::Escape | glfw::Key::Q => self.end_game(),
             glfw::Key::Up | glfw::Key::W => self.camera.move_camera(270.0, 0.1),
             glfw::Key::Down | glfw::Key::S => self.camera.move_camera(90.0, 0.1),
             glfw::Key::Right | glfw::Key::D => self.camera.move_camera(0.0, 0.1),
             glfw::Key::Left | glfw::Key::A => self.camera.move_camera(180.0, 0.1),
             glfw::Key::Minus => self.camera.change_zoom(1.3),
             glfw::Key::Equal => self.camera.change_zoom(0.7),
+            glfw::Key::F5 => match self.game.core() {
+                Some(core) => {
+                    core.save(&self.save_path);
+                    println!("Game saved to {}", self.save_path.display());
+                }
+                None => println!("Network game can not be saved"),
+            },
             _ => {}
         }
-        if self.event_visualizer.is_some() {
+        if self.is_input_blocked() {
             return;
         }
         match key {
             glfw::Key::T => self.end_turn(),
             glfw::Key::U => self.create_unit(),
+            glfw::Key::O => self.overwatch(),
+            glfw::Key::Num0 => self.select_weapon(context, 0),
+            glfw::Key::Num1 => self.select_weapon(context, 1),
+            glfw::Key::Num2 => self.select_weapon(context, 2),
+            glfw::Key::Num3 => self.select_weapon(context, 3),
             _ => {}
         }
     }
@@ -532,24 +1056,16 @@ impl GameStateVisualizer {
             Some(unit_id) => unit_id,
             None => return,
         };
-        if self.is_tile_occupied(pos) {
-            return;
-        }
-        let state = &self.game_states[&self.core.player_id()];
-        let unit = &state.units[&unit_id];
-        if unit.move_points == 0 {
-            return;
-        }
-        let pf = self.pathfinders.get_mut(&self.core.player_id()).unwrap();
-        let path = pf.get_path(pos);
-        if path.len() < 2 {
-            return;
-        }
-        self.core.do_command(CommandMove(unit_id, path));
+        // unreachable tiles have no path
+        let path = match self.pathfinders[&self.game.player_id()].get_path(pos) {
+            Some(path) => path,
+            None => return,
+        };
+        let _ = self.do_command(CommandMove(unit_id, path));
     }
 
     fn handle_mouse_button_event(&mut self, context: &Context) {
-        if self.event_visualizer.is_some() {
+        if self.is_input_blocked() {
             return;
         }
         match self.button_manager.get_clicked_button_id(context) {
@@ -557,7 +1073,7 @@ impl GameStateVisualizer {
                 if button_id == self.button_end_turn_id {
                     self.end_turn();
                 } else if button_id == self.button_quit_id {
-                    self.commands_tx.send(EndGame).unwrap();
+                    self.end_game();
                 } else {
                     println!("Clicked on {} at {}", button_id.id, precise_time_ns());
                 }
@@ -571,11 +1087,11 @@ impl GameStateVisualizer {
         match self.unit_under_cursor_id {
             Some(unit_under_cursor_id) => {
                 let player_id = {
-                    let state = &self.game_states[&self.core.player_id()];
+                    let state = &self.game_states[&self.game.player_id()];
                     let unit = &state.units[&unit_under_cursor_id];
                     unit.player_id.clone()
                 };
-                if player_id == self.core.player_id() {
+                if player_id == self.game.player_id() {
                     self.select_unit(context);
                 } else {
                     self.attack_unit();
@@ -602,72 +1118,171 @@ impl GameStateVisualizer {
         }
     }
 
-    fn make_event_visualizer(&mut self, event: &Event) -> Box<dyn EventVisualizer + 'static> {
-        let player_id = self.core.player_id();
-        let scene = self.scenes.get_mut(&player_id).unwrap();
-        let state = &self.game_states[&player_id];
-        match *event {
-            EventMove(unit_id, ref path) => {
-                let type_id = state.units[&unit_id].type_id;
-                let unit_type_visual_info = self.unit_type_visual_info.get(type_id);
-                EventMoveVisualizer::new(scene, state, unit_id, unit_type_visual_info, path.clone())
-            }
-            EventEndTurn(_, _) => EventEndTurnVisualizer::new(),
-            EventCreateUnit(id, ref pos, ref type_id, ref player_id) => {
-                EventCreateUnitVisualizer::new(
-                    &self.core,
-                    scene,
-                    state,
-                    id,
-                    *type_id,
-                    *pos,
-                    get_unit_mesh_id(&self.unit_type_visual_info, type_id.clone()),
-                    get_marker_mesh_id(&self.mesh_ids, player_id.clone()),
-                )
+    fn get_hint_text(&self) -> Option<String> {
+        let state = &self.game_states[&self.viewer_id()];
+        let attacker = state.units.get(&self.selected_unit_id?)?;
+        let defender = state.units.get(&self.unit_under_cursor_id?)?;
+        if defender.player_id == attacker.player_id {
+            return None;
+        }
+        let object_types = self.game.object_types();
+        // without a weapon in line of fire the main one is shown
+        let weapon_index = get_weapon_index(
+            object_types,
+            state,
+            attacker,
+            defender,
+            self.selected_weapon_index,
+        )
+        .unwrap_or(0);
+        let weapon_type = object_types.get_unit_weapon_type(attacker.type_id, weapon_index);
+        let prediction = predict(object_types, attacker, defender, weapon_index);
+        let mut text = format!(
+            "{}: kill chance: {}%",
+            weapon_type.name, prediction.any_kill
+        );
+        if defender.count > 1 {
+            text.push_str(&format!(", destroy chance: {}%", prediction.destroy));
+        }
+        if prediction.shots > 1 {
+            text.push_str(&format!(", {} shots", prediction.shots));
+        }
+        match check_line_of_fire(object_types, state, attacker, weapon_index, defender.pos) {
+            Ok(()) => {}
+            Err(err) => text.push_str(&format!(" ({})", err)),
+        }
+        Some(text)
+    }
+
+    // Label is rebuilt only when its text changes
+    fn update_hint(&mut self, context: &Context) {
+        let hint_text = self.get_hint_text();
+        if hint_text == self.hint_text {
+            return;
+        }
+        self.hint_button_manager = ButtonManager::new();
+        match hint_text {
+            Some(ref text) => {
+                let _ = self.hint_button_manager.add_button(Button::new(
+                    text,
+                    context.font_stash.borrow_mut().deref_mut(),
+                    &context.shader,
+                    ScreenPos {
+                        v: Vector2 { x: 10, y: 70 },
+                    },
+                ));
             }
-            EventAttackUnit(attacker_id, defender_id, killed) => EventAttackUnitVisualizer::new(
-                scene,
-                state,
-                attacker_id,
-                defender_id,
-                killed,
-                self.mesh_ids.shell_mesh_id,
-            ),
+            None => {}
         }
+        self.hint_text = hint_text;
     }
 
-    fn start_event_visualization(&mut self, event: Event) {
-        let vis = self.make_event_visualizer(&event);
+    fn start_event_visualization(&mut self, context: &Context, event: Event) {
+        self.attack_result_button_manager = ButtonManager::new();
+        let state = &self.game_states[&self.game.player_id()];
+        let text = match event {
+            EventAttackUnit(attacker_id, _, ref result) => {
+                let outcomes: Vec<String> = result
+                    .shots
+                    .iter()
+                    .map(|shot| shot.outcome.to_string().to_uppercase())
+                    .collect();
+                let attacker = &state.units[&attacker_id];
+                let weapon_type = self
+                    .game
+                    .object_types()
+                    .get_unit_weapon_type(attacker.type_id, result.weapon_index);
+                let text = format!(
+                    "{}: {}",
+                    weapon_type.name.to_uppercase(),
+                    outcomes.join(", ")
+                );
+                if attacker.overwatch {
+                    Some(format!("REACTION FIRE, {}", text))
+                } else {
+                    Some(text)
+                }
+            }
+            EventOverwatch(_) => Some("OVERWATCH".to_string()),
+            _ => None,
+        };
+        match text {
+            Some(ref text) => {
+                let _ = self.attack_result_button_manager.add_button(Button::new(
+                    text,
+                    context.font_stash.borrow_mut().deref_mut(),
+                    &context.shader,
+                    ScreenPos {
+                        v: Vector2 { x: 10, y: 100 },
+                    },
+                ));
+            }
+            None => {}
+        }
+        let player_id = self.game.player_id();
+        let vis = make_event_visualizer(
+            self.game.object_types(),
+            self.scenes.get_mut(&player_id).unwrap(),
+            &self.game_states[&player_id],
+            &self.unit_type_visual_info,
+            &self.mesh_ids,
+            &event,
+        );
         self.event = Some(event);
         self.event_visualizer = Some(vis);
     }
 
+    // Events of computer players only update their states,
+    // humans see these moves later through their own events
+    fn apply_ai_event(&mut self, event: Event) {
+        let state = self.game_states.get_mut(&self.game.player_id()).unwrap();
+        state.apply_event(self.game.object_types(), &event);
+        if let EventGameOver(result) = event {
+            self.show_game_over(result);
+        }
+    }
+
     fn end_event_visualization(&mut self, context: &Context) {
-        let scene = self.scenes.get_mut(&self.core.player_id()).unwrap();
-        let state = self.game_states.get_mut(&self.core.player_id()).unwrap();
+        let scene = self.scenes.get_mut(&self.game.player_id()).unwrap();
+        let state = self.game_states.get_mut(&self.game.player_id()).unwrap();
         self.event_visualizer.as_mut().unwrap().end(scene, state);
-        state.apply_event(self.core.object_types(), self.event.as_ref().unwrap());
+        let event = self.event.take().unwrap();
+        state.apply_event(self.game.object_types(), &event);
         self.event_visualizer = None;
-        self.event = None;
-        match self.selected_unit_id {
-            Some(selected_unit_id) => {
-                let pf = self.pathfinders.get_mut(&self.core.player_id()).unwrap();
-                pf.fill_map(state, &state.units[&selected_unit_id]);
-                self.walkable_mesh = Some(build_walkable_mesh(pf, &context.shader));
+        // selected unit could be killed by reaction fire
+        match self.selected_unit_id.and_then(|id| state.units.get(&id)) {
+            Some(selected_unit) => {
+                let pf = self.pathfinders.get_mut(&self.game.player_id()).unwrap();
+                pf.fill_map(self.game.object_types(), state, selected_unit);
+                self.walkable_mesh = Some(build_walkable_mesh(pf, false, &context.shader));
+                self.stop_walkable_mesh = Some(build_walkable_mesh(pf, true, &context.shader));
                 self.selection_manager.move_selection_marker(state, scene);
             }
-            None => {}
+            None => {
+                self.selected_unit_id = None;
+                self.selection_manager.deselect(scene);
+                self.walkable_mesh = None;
+                self.stop_walkable_mesh = None;
+            }
         }
         self.picker.update_units(state);
+        self.update_targets_meshes(&context.shader);
+        if let EventGameOver(result) = event {
+            self.show_game_over(result);
+        }
     }
 }
 
 impl StateVisualizer for GameStateVisualizer {
     fn logic(&mut self, context: &Context) {
         if self.event_visualizer.is_none() {
-            match self.core.get_event() {
-                Some(e) => self.start_event_visualization(e),
-                None => {}
+            if self.start_handover() {
+                return;
+            }
+            match self.get_event() {
+                Some(e) if self.is_ai_turn() => self.apply_ai_event(e),
+                Some(e) => self.start_event_visualization(context, e),
+                None => self.do_ai_command(),
             }
         } else if self.event_visualizer.as_ref().unwrap().is_finished() {
             self.end_event_visualization(context);
@@ -675,7 +1290,15 @@ impl StateVisualizer for GameStateVisualizer {
     }
 
     fn draw(&mut self, context: &mut Context, dtime: Time) {
+        if self.shown_player_id != Some(self.viewer_id()) {
+            mgl::set_clear_color(mgl::BLACK_3);
+            mgl::clear_screen();
+            use glfw::Context;
+            context.win.swap_buffers();
+            return;
+        }
         self.pick_tile(context);
+        self.update_hint(context);
         mgl::set_clear_color(mgl::GREY_3);
         mgl::clear_screen();
         context.shader.activate();
@@ -685,6 +1308,8 @@ impl StateVisualizer for GameStateVisualizer {
             .uniform_color(context.basic_color_id, mgl::BLACK);
         self.draw_3d_text(context);
         self.button_manager.draw(context);
+        self.hint_button_manager.draw(context);
+        self.attack_result_button_manager.draw(context);
         use glfw::Context;
         context.win.swap_buffers();
     }
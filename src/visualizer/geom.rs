@@ -1,9 +1,9 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::misc::rad_to_deg;
-use crate::core::types::{MInt, MapPos};
+use crate::visualizer::misc::rad_to_deg;
 use crate::visualizer::types::{MFloat, VertexCoord, WorldPos};
 use cgmath::{Vector, Vector2, Vector3};
+use marauder_core::types::{MInt, MapPos};
 use std::f32::consts::{FRAC_PI_2, PI};
 
 pub const HEX_EX_RADIUS: MFloat = 1.0;
@@ -1,6 +1,5 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::types::{MInt, Size2};
 use crate::visualizer::context::Context;
 use crate::visualizer::font_stash::FontStash;
 use crate::visualizer::mesh::Mesh;
@@ -8,6 +7,7 @@ use crate::visualizer::mgl;
 use crate::visualizer::shader::Shader;
 use crate::visualizer::types::{MFloat, ScreenPos};
 use cgmath::Vector3;
+use marauder_core::types::{MInt, Size2};
 use std::collections::HashMap;
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone)]
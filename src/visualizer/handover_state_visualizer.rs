@@ -0,0 +1,213 @@
+// See LICENSE file for copyright and license details.
+
+use crate::visualizer::context::Context;
+use crate::visualizer::gui::{Button, ButtonId, ButtonManager};
+use crate::visualizer::mgl;
+use crate::visualizer::state_visualizer::StateChangeCommand::EndHandover;
+use crate::visualizer::state_visualizer::{StateChangeCommand, StateVisualizer};
+use crate::visualizer::types::{ScreenPos, Time};
+use cgmath::Vector2;
+use marauder_core::core::Event::{
+    EventAttackUnit, EventCreateUnit, EventHideUnit, EventOverwatch, EventShowUnit,
+};
+use marauder_core::core::{Event, ObjectTypes, Unit};
+use marauder_core::game_state::GameState;
+use marauder_core::types::{MInt, MapPos, PlayerId, UnitId};
+use std::ops::DerefMut;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+fn format_pos(pos: MapPos) -> String {
+    format!("{},{}", pos.v.x, pos.v.y)
+}
+
+fn get_unit_name(object_types: &ObjectTypes, unit: &Unit) -> String {
+    let unit_type = object_types.get_unit_type(unit.type_id);
+    format!("{} {}", unit_type.name, unit.id.id)
+}
+
+fn get_attacker_name(object_types: &ObjectTypes, state: &GameState, id: UnitId) -> String {
+    match state.units.get(&id) {
+        Some(unit) => get_unit_name(object_types, unit),
+        None => "unseen enemy".to_string(),
+    }
+}
+
+// What happened to player's units during other players' turns.
+// State is player's view before the events.
+pub fn get_events_summary(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    player_id: &PlayerId,
+    events: &[Event],
+) -> Vec<String> {
+    let mut state = state.clone();
+    let mut lines = Vec::new();
+    for event in events.iter() {
+        match *event {
+            EventAttackUnit(attacker_id, defender_id, ref result)
+                if state.units[&defender_id].player_id == *player_id =>
+            {
+                let defender = &state.units[&defender_id];
+                let attacker_name = get_attacker_name(object_types, &state, attacker_id);
+                let defender_name = get_unit_name(object_types, defender);
+                let pos = format_pos(defender.pos);
+                lines.push(if result.is_destroyed(defender) {
+                    format!(
+                        "{} at {} was destroyed by {}",
+                        defender_name, pos, attacker_name
+                    )
+                } else if result.killed_count() > 0 {
+                    format!(
+                        "{} at {} lost {} of {} models to {}",
+                        defender_name,
+                        pos,
+                        result.killed_count(),
+                        defender.count,
+                        attacker_name,
+                    )
+                } else {
+                    format!(
+                        "{} at {} survived attack by {}",
+                        defender_name, pos, attacker_name
+                    )
+                });
+            }
+            EventCreateUnit(_, pos, type_id, ref owner_id) if *owner_id != *player_id => {
+                lines.push(format!(
+                    "enemy {} appeared at {}",
+                    object_types.get_unit_type(type_id).name,
+                    format_pos(pos),
+                ));
+            }
+            EventShowUnit(ref unit) if unit.player_id != *player_id => {
+                lines.push(format!(
+                    "enemy {} spotted at {}",
+                    get_unit_name(object_types, unit),
+                    format_pos(unit.pos),
+                ));
+            }
+            EventOverwatch(id) if state.units[&id].player_id != *player_id => {
+                let unit = &state.units[&id];
+                lines.push(format!(
+                    "enemy {} at {} is on overwatch",
+                    get_unit_name(object_types, unit),
+                    format_pos(unit.pos),
+                ));
+            }
+            EventHideUnit(id) => {
+                let unit = &state.units[&id];
+                lines.push(format!(
+                    "lost sight of enemy {}",
+                    get_unit_name(object_types, unit)
+                ));
+            }
+            _ => {}
+        }
+        state.apply_event(object_types, event);
+    }
+    if lines.is_empty() {
+        lines.push("nothing happened to your units".to_string());
+    }
+    lines
+}
+
+// Covers the board between hotseat turns, so the next player
+// does not see the scene of the previous one
+pub struct HandoverStateVisualizer {
+    button_manager: ButtonManager,
+    button_continue_id: ButtonId,
+    commands_rx: Receiver<StateChangeCommand>,
+    commands_tx: Sender<StateChangeCommand>,
+}
+
+impl HandoverStateVisualizer {
+    pub fn new(
+        context: &Context,
+        player_name: &str,
+        summary: &[String],
+    ) -> HandoverStateVisualizer {
+        let mut button_manager = ButtonManager::new();
+        let mut lines = vec![format!("{}'s turn", player_name)];
+        lines.extend(summary.iter().cloned());
+        // text lines are buttons that do nothing
+        for (i, line) in lines.iter().enumerate() {
+            let _ = button_manager.add_button(Button::new(
+                line,
+                context.font_stash.borrow_mut().deref_mut(),
+                &context.shader,
+                ScreenPos {
+                    v: Vector2 {
+                        x: 10,
+                        y: context.win_size.h - 40 - i as MInt * 30,
+                    },
+                },
+            ));
+        }
+        let button_continue_id = button_manager.add_button(Button::new(
+            "continue",
+            context.font_stash.borrow_mut().deref_mut(),
+            &context.shader,
+            ScreenPos {
+                v: Vector2 { x: 10, y: 10 },
+            },
+        ));
+        let (commands_tx, commands_rx) = channel();
+        HandoverStateVisualizer {
+            button_manager,
+            button_continue_id,
+            commands_rx,
+            commands_tx,
+        }
+    }
+
+    fn handle_mouse_button_event(&mut self, context: &Context) {
+        match self.button_manager.get_clicked_button_id(context) {
+            Some(button_id) => {
+                if button_id == self.button_continue_id {
+                    self.commands_tx.send(EndHandover).unwrap();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+impl StateVisualizer for HandoverStateVisualizer {
+    fn logic(&mut self, _: &Context) {}
+
+    fn draw(&mut self, context: &mut Context, _: Time) {
+        mgl::set_clear_color(mgl::BLACK_3);
+        mgl::clear_screen();
+        context.shader.activate();
+        context
+            .shader
+            .uniform_color(context.basic_color_id, mgl::WHITE);
+        self.button_manager.draw(context);
+        use glfw::Context;
+        context.win.swap_buffers();
+    }
+
+    fn handle_event(&mut self, context: &Context, event: glfw::WindowEvent) {
+        match event {
+            glfw::WindowEvent::Key(key, _, glfw::Action::Press, _) => match key {
+                glfw::Key::Enter | glfw::Key::Space => {
+                    self.commands_tx.send(EndHandover).unwrap();
+                }
+                _ => {}
+            },
+            glfw::WindowEvent::MouseButton(glfw::MouseButtonLeft, glfw::Action::Press, _) => {
+                self.handle_mouse_button_event(context);
+            }
+            _ => {}
+        }
+    }
+
+    fn get_command(&self) -> Option<StateChangeCommand> {
+        match self.commands_rx.try_recv() {
+            Ok(cmd) => Some(cmd),
+            Err(_) => None,
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
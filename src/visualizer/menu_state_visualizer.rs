@@ -3,7 +3,9 @@
 use crate::visualizer::context::Context;
 use crate::visualizer::gui::{Button, ButtonId, ButtonManager};
 use crate::visualizer::mgl;
-use crate::visualizer::state_visualizer::StateChangeCommand::{QuitMenu, StartGame};
+use crate::visualizer::state_visualizer::StateChangeCommand::{
+    LoadGame, QuitMenu, StartGame, StartReplay,
+};
 use crate::visualizer::state_visualizer::{StateChangeCommand, StateVisualizer};
 use crate::visualizer::types::{ScreenPos, Time};
 use cgmath::Vector2;
@@ -13,6 +15,8 @@ use std::sync::mpsc::{channel, Receiver, Sender};
 pub struct MenuStateVisualizer {
     button_manager: ButtonManager,
     button_start_id: ButtonId,
+    button_load_id: ButtonId,
+    button_replay_id: ButtonId,
     button_quit_id: ButtonId,
     commands_rx: Receiver<StateChangeCommand>,
     commands_tx: Sender<StateChangeCommand>,
@@ -21,6 +25,22 @@ pub struct MenuStateVisualizer {
 impl MenuStateVisualizer {
     pub fn new(context: &Context) -> MenuStateVisualizer {
         let mut button_manager = ButtonManager::new();
+        let button_replay_id = button_manager.add_button(Button::new(
+            "replay",
+            context.font_stash.borrow_mut().deref_mut(),
+            &context.shader,
+            ScreenPos {
+                v: Vector2 { x: 10, y: 100 },
+            },
+        ));
+        let button_load_id = button_manager.add_button(Button::new(
+            "load",
+            context.font_stash.borrow_mut().deref_mut(),
+            &context.shader,
+            ScreenPos {
+                v: Vector2 { x: 10, y: 70 },
+            },
+        ));
         let button_start_id = button_manager.add_button(Button::new(
             "start",
             context.font_stash.borrow_mut().deref_mut(),
@@ -41,6 +61,8 @@ impl MenuStateVisualizer {
         MenuStateVisualizer {
             button_manager,
             button_start_id,
+            button_load_id,
+            button_replay_id,
             button_quit_id,
             commands_rx,
             commands_tx,
@@ -52,6 +74,10 @@ impl MenuStateVisualizer {
             Some(button_id) => {
                 if button_id == self.button_start_id {
                     self.commands_tx.send(StartGame).unwrap();
+                } else if button_id == self.button_load_id {
+                    self.commands_tx.send(LoadGame).unwrap();
+                } else if button_id == self.button_replay_id {
+                    self.commands_tx.send(StartReplay).unwrap();
                 } else if button_id == self.button_quit_id {
                     self.commands_tx.send(QuitMenu).unwrap();
                 }
@@ -82,6 +108,12 @@ impl StateVisualizer for MenuStateVisualizer {
                 glfw::Key::Kp1 => {
                     self.commands_tx.send(StartGame).unwrap();
                 }
+                glfw::Key::Kp2 => {
+                    self.commands_tx.send(LoadGame).unwrap();
+                }
+                glfw::Key::Kp3 => {
+                    self.commands_tx.send(StartReplay).unwrap();
+                }
                 glfw::Key::Escape | glfw::Key::Q => {
                     self.commands_tx.send(QuitMenu).unwrap();
                 }
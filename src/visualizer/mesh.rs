@@ -1,11 +1,11 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::types::MInt;
 use crate::visualizer::mgl::MeshRenderMode::Triangles;
 use crate::visualizer::mgl::{MeshRenderMode, Vao, Vbo};
 use crate::visualizer::shader::Shader;
 use crate::visualizer::texture::Texture;
 use crate::visualizer::types::{Color3, TextureCoord, VertexCoord};
+use marauder_core::types::MInt;
 
 #[derive(Clone, Copy)]
 pub struct MeshId {
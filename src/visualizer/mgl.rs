@@ -2,11 +2,11 @@
 
 #![macro_use]
 
-use crate::core::misc::deg_to_rad;
-use crate::core::types::{MInt, Size2};
+use crate::visualizer::misc::deg_to_rad;
 use crate::visualizer::types::{Color3, Color4, MFloat, ScreenPos};
 use cgmath::{ortho, rad, Matrix, Matrix3, Matrix4, SquareMatrix, Vector3};
 use gl::types::{GLsizeiptr, GLuint};
+use marauder_core::types::{MInt, Size2};
 
 pub const GREY_3: Color3 = Color3 {
     r: 0.3,
@@ -30,6 +30,18 @@ pub const BLUE: Color4 = Color4 {
     b: 1.0,
     a: 1.0,
 };
+pub const RED: Color4 = Color4 {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+pub const YELLOW: Color4 = Color4 {
+    r: 1.0,
+    g: 1.0,
+    b: 0.0,
+    a: 1.0,
+};
 pub const BLACK: Color4 = Color4 {
     r: 0.0,
     g: 0.0,
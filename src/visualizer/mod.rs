@@ -4,14 +4,18 @@ pub mod camera;
 pub mod context;
 pub mod event_visualizer;
 pub mod font_stash;
+pub mod game_over_state_visualizer;
 pub mod game_state_visualizer;
 pub mod geom;
 pub mod gui;
+pub mod handover_state_visualizer;
 pub mod menu_state_visualizer;
 pub mod mesh;
 pub mod mgl;
+pub mod misc;
 pub mod obj;
 pub mod picker;
+pub mod replay_state_visualizer;
 pub mod scene;
 pub mod selection;
 pub mod shader;
@@ -1,8 +1,8 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::types::MInt;
 use crate::visualizer::types::{Normal, TextureCoord, VertexCoord};
 use cgmath::{Vector2, Vector3};
+use marauder_core::types::MInt;
 
 use std::fs::File;
 use std::io::{BufRead, BufReader};
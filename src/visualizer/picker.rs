@@ -1,9 +1,5 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::fs::FileSystem;
-use crate::core::game_state::GameState;
-use crate::core::map::MapPosIter;
-use crate::core::types::{MInt, MapPos, Size2, UnitId};
 use crate::visualizer::camera::Camera;
 use crate::visualizer::mesh::Mesh;
 use crate::visualizer::picker::PickResult::{PickedMapPos, PickedNothing, PickedUnitId};
@@ -11,6 +7,10 @@ use crate::visualizer::shader::Shader;
 use crate::visualizer::types::{Color3, MFloat, MatId, ScreenPos, VertexCoord};
 use crate::visualizer::{geom, mgl};
 use cgmath::Vector2;
+use marauder_core::fs::FileSystem;
+use marauder_core::game_state::GameState;
+use marauder_core::map::MapPosIter;
+use marauder_core::types::{MInt, MapPos, Size2, UnitId};
 use std::path::Path;
 
 const PICK_CODE_NOTHING: MInt = 0;
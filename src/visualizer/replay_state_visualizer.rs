@@ -0,0 +1,219 @@
+// See LICENSE file for copyright and license details.
+
+use marauder_core::core::{Event, ObjectTypes};
+use marauder_core::fs::FileSystem;
+use marauder_core::game_state::GameState;
+use marauder_core::replay::Replay;
+use marauder_core::types::{MInt, Size2};
+use crate::visualizer::camera::Camera;
+use crate::visualizer::context::Context;
+use crate::visualizer::event_visualizer::EventVisualizer;
+use crate::visualizer::game_state_visualizer::{
+    draw_map, draw_scene_node, get_initial_camera_pos, get_max_camera_pos, get_mesh_ids,
+    get_unit_type_visual_info, make_event_visualizer, MeshIdManager,
+};
+use crate::visualizer::mesh::Mesh;
+use crate::visualizer::mgl;
+use crate::visualizer::scene::Scene;
+use crate::visualizer::state_visualizer::StateChangeCommand::EndGame;
+use crate::visualizer::state_visualizer::{StateChangeCommand, StateVisualizer};
+use crate::visualizer::types::{MFloat, ScreenPos, Time};
+use crate::visualizer::unit_type_visual_info::UnitTypeVisualInfoManager;
+use cgmath::Vector2;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+const MIN_SPEED: MFloat = 0.25;
+const MAX_SPEED: MFloat = 8.0;
+
+// Plays core events from a replay file, everything is visible
+pub struct ReplayStateVisualizer {
+    object_types: ObjectTypes,
+    mesh_ids: MeshIdManager,
+    unit_type_visual_info: UnitTypeVisualInfoManager,
+    meshes: Vec<Mesh>,
+    camera: Camera,
+    scene: Scene,
+    state: GameState,
+    events: Vec<Event>,
+    next_event_index: usize,
+    event: Option<Event>,
+    event_visualizer: Option<Box<dyn EventVisualizer + 'static>>,
+    is_paused: bool,
+    // play one event while paused
+    do_step: bool,
+    speed: MFloat,
+    commands_rx: Receiver<StateChangeCommand>,
+    commands_tx: Sender<StateChangeCommand>,
+}
+
+impl ReplayStateVisualizer {
+    pub fn new(fs: &FileSystem, context: &Context, replay_path: &Path) -> ReplayStateVisualizer {
+        let replay = Replay::load(replay_path);
+        let object_types = ObjectTypes::new(&fs.get(&Path::new("data/object_types.json")));
+        let map_size = replay.map.get_size();
+        let mut meshes = Vec::new();
+        let mesh_ids = get_mesh_ids(
+            fs,
+            &context.shader,
+            &object_types,
+            &replay.map,
+            replay.players.len() as MInt,
+            &mut meshes,
+        );
+        let unit_type_visual_info =
+            get_unit_type_visual_info(fs, context, &object_types, &mut meshes);
+        let mut camera = Camera::new(context.win_size);
+        camera.set_max_pos(get_max_camera_pos(&map_size));
+        camera.set_pos(get_initial_camera_pos(&map_size));
+        let (commands_tx, commands_rx) = channel();
+        println!("Replay: seed {}", replay.seed);
+        ReplayStateVisualizer {
+            object_types,
+            mesh_ids,
+            unit_type_visual_info,
+            meshes,
+            camera,
+            scene: Scene::new(),
+            events: replay.events(),
+            state: GameState::new(replay.map),
+            next_event_index: 0,
+            event: None,
+            event_visualizer: None,
+            is_paused: false,
+            do_step: false,
+            speed: 1.0,
+            commands_rx,
+            commands_tx,
+        }
+    }
+
+    fn start_event_visualization(&mut self) {
+        if self.next_event_index >= self.events.len() {
+            return;
+        }
+        let event = self.events[self.next_event_index].clone();
+        self.next_event_index += 1;
+        let vis = make_event_visualizer(
+            &self.object_types,
+            &mut self.scene,
+            &self.state,
+            &self.unit_type_visual_info,
+            &self.mesh_ids,
+            &event,
+        );
+        self.event = Some(event);
+        self.event_visualizer = Some(vis);
+    }
+
+    fn end_event_visualization(&mut self) {
+        self.event_visualizer
+            .as_mut()
+            .unwrap()
+            .end(&mut self.scene, &self.state);
+        self.state
+            .apply_event(&self.object_types, self.event.as_ref().unwrap());
+        self.event_visualizer = None;
+        self.event = None;
+    }
+
+    fn change_speed(&mut self, ratio: MFloat) {
+        self.speed = (self.speed * ratio).clamp(MIN_SPEED, MAX_SPEED);
+        println!("Replay speed: {}", self.speed);
+    }
+
+    fn handle_key_event(&mut self, key: glfw::Key) {
+        match key {
+            glfw::Key::Escape | glfw::Key::Q => self.commands_tx.send(EndGame).unwrap(),
+            glfw::Key::Up | glfw::Key::W => self.camera.move_camera(270.0, 0.1),
+            glfw::Key::Down | glfw::Key::S => self.camera.move_camera(90.0, 0.1),
+            glfw::Key::Right | glfw::Key::D => self.camera.move_camera(0.0, 0.1),
+            glfw::Key::Left | glfw::Key::A => self.camera.move_camera(180.0, 0.1),
+            glfw::Key::Minus => self.camera.change_zoom(1.3),
+            glfw::Key::Equal => self.camera.change_zoom(0.7),
+            glfw::Key::Space => self.is_paused = !self.is_paused,
+            glfw::Key::N => self.do_step = true,
+            glfw::Key::LeftBracket => self.change_speed(0.5),
+            glfw::Key::RightBracket => self.change_speed(2.0),
+            _ => {}
+        }
+    }
+
+    fn handle_cursor_pos_event(&mut self, context: &Context, new_pos: ScreenPos) {
+        let rmb = context.win.get_mouse_button(glfw::MouseButtonRight);
+        if rmb == glfw::Action::Press {
+            let diff = context.mouse_pos.v - new_pos.v;
+            let win_w = context.win_size.w as MFloat;
+            let win_h = context.win_size.h as MFloat;
+            self.camera.add_z_angle(diff.x as MFloat * (360.0 / win_w));
+            self.camera.add_x_angle(diff.y as MFloat * (360.0 / win_h));
+        }
+    }
+}
+
+impl StateVisualizer for ReplayStateVisualizer {
+    fn logic(&mut self, _: &Context) {
+        if self.event_visualizer.is_none() {
+            if !self.is_paused || self.do_step {
+                self.do_step = false;
+                self.start_event_visualization();
+            }
+        } else if self.event_visualizer.as_ref().unwrap().is_finished() {
+            self.end_event_visualization();
+        }
+    }
+
+    fn draw(&mut self, context: &mut Context, dtime: Time) {
+        mgl::set_clear_color(mgl::GREY_3);
+        mgl::clear_screen();
+        context.shader.activate();
+        context
+            .shader
+            .uniform_color(context.basic_color_id, mgl::WHITE);
+        for (_, node) in self.scene.nodes.iter() {
+            draw_scene_node(&self.meshes, node, self.camera.mat(), context);
+        }
+        draw_map(&self.meshes, &self.mesh_ids, &self.camera, context);
+        match self.event_visualizer {
+            Some(ref mut event_visualizer) => {
+                let dtime = Time {
+                    n: (dtime.n as MFloat * self.speed) as u64,
+                };
+                event_visualizer.draw(&mut self.scene, dtime);
+            }
+            None => {}
+        }
+        use glfw::Context;
+        context.win.swap_buffers();
+    }
+
+    fn handle_event(&mut self, context: &Context, event: glfw::WindowEvent) {
+        match event {
+            glfw::WindowEvent::Key(key, _, glfw::Action::Press, _) => {
+                self.handle_key_event(key);
+            }
+            glfw::WindowEvent::CursorPos(x, y) => {
+                let p = ScreenPos {
+                    v: Vector2 {
+                        x: x as MInt,
+                        y: y as MInt,
+                    },
+                };
+                self.handle_cursor_pos_event(context, p);
+            }
+            glfw::WindowEvent::Size(w, h) => {
+                self.camera.regenerate_projection_mat(Size2 { w, h });
+            }
+            _ => {}
+        }
+    }
+
+    fn get_command(&self) -> Option<StateChangeCommand> {
+        match self.commands_rx.try_recv() {
+            Ok(cmd) => Some(cmd),
+            Err(_) => None,
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -1,8 +1,8 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::types::MInt;
 use crate::visualizer::mesh::MeshId;
 use crate::visualizer::types::{MFloat, WorldPos};
+use marauder_core::types::MInt;
 use std::collections::HashMap;
 
 // TODO: why scene knows about other systems?
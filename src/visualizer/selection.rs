@@ -1,16 +1,16 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::fs::FileSystem;
-use crate::core::game_state::GameState;
-use crate::core::misc::add_quad_to_vec;
-use crate::core::types::UnitId;
 use crate::visualizer::geom;
 use crate::visualizer::mesh::{Mesh, MeshId};
+use crate::visualizer::misc::add_quad_to_vec;
 use crate::visualizer::scene::{Scene, SceneNode, SELECTION_NODE_ID};
 use crate::visualizer::shader::Shader;
 use crate::visualizer::texture::Texture;
 use crate::visualizer::types::{TextureCoord, WorldPos};
 use cgmath::Vector2;
+use marauder_core::fs::FileSystem;
+use marauder_core::game_state::GameState;
+use marauder_core::types::UnitId;
 use std::path::Path;
 
 pub struct SelectionManager {
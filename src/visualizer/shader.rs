@@ -1,12 +1,12 @@
 // See LICENSE file for copyright and license details.
 
 use crate::cgmath::Matrix;
-use crate::core::misc::read_file;
-use crate::core::types::MInt;
 use crate::visualizer::types::{Color4, ColorId, MFloat, MatId};
 use cgmath::Matrix4;
 use gl::types::{GLchar, GLenum, GLint, GLuint};
 use glfw::with_c_str;
+use marauder_core::misc::read_file;
+use marauder_core::types::MInt;
 use std::path::Path;
 
 pub struct Shader {
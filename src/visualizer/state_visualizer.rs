@@ -1,13 +1,21 @@
 // See LICENSE file for copyright and license details.
 
-use crate::visualizer::types::Time;
 use crate::visualizer::context::Context;
+use crate::visualizer::types::Time;
 use glfw::WindowEvent;
+use marauder_core::core::Player;
+use marauder_core::victory::GameResult;
 
 pub enum StateChangeCommand {
     StartGame,
+    LoadGame,
+    StartReplay,
     QuitMenu,
     EndGame,
+    ShowGameOver(Vec<Player>, GameResult),
+    // player name and summary of events since its last turn
+    ShowHandover(String, Vec<String>),
+    EndHandover,
 }
 
 pub trait StateVisualizer {
@@ -1,9 +1,10 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::types::{MInt, Size2};
 use crate::visualizer::shader::Shader;
+use crate::visualizer::types::{Color3, MFloat};
 use cgmath::Vector2;
 use gl::types::{GLint, GLsizei, GLuint};
+use marauder_core::types::{MInt, Size2};
 use stb_image::image;
 use std::path::Path;
 
@@ -22,6 +23,10 @@ impl Texture {
         get_empty_texture(size)
     }
 
+    pub fn new_color(color: Color3) -> Texture {
+        get_color_texture(color)
+    }
+
     pub fn enable(&self, shader: &Shader) {
         let basic_texture_loc = shader.get_uniform("basic_texture") as GLint;
         unsafe {
@@ -105,6 +110,19 @@ fn get_empty_texture(size: Size2<MInt>) -> Texture {
     Texture { id }
 }
 
+fn get_color_texture(color: Color3) -> Texture {
+    let size = Size2 { w: 2, h: 2 };
+    let texture = get_empty_texture(size);
+    let to_byte = |c: MFloat| (c * 255.0) as u8;
+    let pixel = [to_byte(color.r), to_byte(color.g), to_byte(color.b), 255];
+    let mut data = Vec::new();
+    for _ in 0..(size.w * size.h) {
+        data.extend_from_slice(&pixel);
+    }
+    texture.set_sub_image(Vector2 { x: 0, y: 0 }, size, &data);
+    texture
+}
+
 fn load_texture(path: &Path) -> Texture {
     let image = load_image(path);
     let mut id = 0;
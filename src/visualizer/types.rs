@@ -1,8 +1,8 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::types::MInt;
 use cgmath::{Vector2, Vector3};
 use gl::types::{GLfloat, GLuint};
+use marauder_core::types::MInt;
 
 pub type MFloat = GLfloat;
 
@@ -1,8 +1,8 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::core::UnitTypeId;
 use crate::visualizer::mesh::MeshId;
 use crate::visualizer::types::MFloat;
+use marauder_core::core::UnitTypeId;
 
 pub struct UnitTypeVisualInfo {
     pub mesh_id: MeshId,
@@ -1,19 +1,25 @@
 // See LICENSE file for copyright and license details.
 
-use crate::core::conf::Config;
-use crate::core::fs::FileSystem;
-use crate::core::types::{MInt, Size2};
 use crate::visualizer::context::Context;
 use crate::visualizer::font_stash::FontStash;
+use crate::visualizer::game_over_state_visualizer::GameOverStateVisualizer;
 use crate::visualizer::game_state_visualizer::GameStateVisualizer;
+use crate::visualizer::handover_state_visualizer::HandoverStateVisualizer;
 use crate::visualizer::menu_state_visualizer::MenuStateVisualizer;
 use crate::visualizer::mgl;
+use crate::visualizer::replay_state_visualizer::ReplayStateVisualizer;
 use crate::visualizer::shader::Shader;
 use crate::visualizer::state_visualizer::{StateVisualizer, StateChangeCommand};
 use crate::visualizer::types::{ColorId, MFloat, MatId, ScreenPos, Time};
 use cgmath::{Vector, Vector2};
+use marauder_core::conf::Config;
+use marauder_core::core::Core;
+use marauder_core::fs::FileSystem;
+use marauder_core::game::Game;
+use marauder_core::net::RemoteGame;
+use marauder_core::types::{MInt, PlayerId, Size2};
 use std::cell::RefCell;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
 use time::precise_time_ns;
 
@@ -27,6 +33,13 @@ pub struct Visualizer {
     events: EventsReceiver,
     context: Context,
     fs: FileSystem,
+    scenario_path: PathBuf,
+    seed: Option<u64>,
+    // new game joins this server instead of starting local one
+    server_address: Option<String>,
+    player_id: Option<PlayerId>,
+    save_path: PathBuf,
+    replay_path: PathBuf,
     should_close: bool,
 }
 
@@ -39,11 +52,17 @@ fn create_win(glfw: &glfw::Glfw, win_size: Size2<MInt>) -> (glfw::Window, Events
 }
 
 impl Visualizer {
-    pub fn new() -> Visualizer {
+    pub fn new(
+        scenario_path: &Path,
+        seed: Option<u64>,
+        server_address: Option<String>,
+        player_id: Option<PlayerId>,
+    ) -> Visualizer {
         let fs = FileSystem::new();
         let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
         let config = Config::new(&fs.get(&Path::new("data/conf_visualizer.json")));
-        let win_size: Size2<MInt> = serde_json::from_value(config.get("screen_size").clone()).unwrap();
+        let win_size: Size2<MInt> =
+            serde_json::from_value(config.get("screen_size").clone()).unwrap();
         let (mut win, events) = create_win(&glfw, win_size);
         glfw.make_context_current(Some(&win));
         gl::load_with(|procname| win.get_proc_address(procname));
@@ -85,6 +104,12 @@ impl Visualizer {
             glfw,
             events,
             context,
+            scenario_path: scenario_path.to_path_buf(),
+            seed,
+            server_address,
+            player_id,
+            save_path: fs.get(&Path::new("save.json")),
+            replay_path: fs.get(&Path::new("replay.json")),
             fs,
             should_close: false,
         }
@@ -103,18 +128,81 @@ impl Visualizer {
         !self.should_close
     }
 
+    fn start_game(&mut self, game: Box<dyn Game>) {
+        let visualizer = Box::new(GameStateVisualizer::new(
+            &self.fs,
+            &self.context,
+            game,
+            &self.save_path,
+            &self.replay_path,
+        ));
+        self.visualizers
+            .push(visualizer as Box<dyn StateVisualizer>);
+    }
+
     fn handle_cmd(&mut self) {
         let cmd = match self.visualizers.last() {
             Some(visualizer) => visualizer.get_command(),
             None => panic!("No state visualizer"),
         };
         match cmd {
-            Some(StateChangeCommand::StartGame) => {
-                let visualizer = Box::new(GameStateVisualizer::new(&self.fs, &self.context));
+            Some(StateChangeCommand::StartGame) => match self.server_address {
+                Some(ref address) => {
+                    match RemoteGame::connect(&self.fs, address, self.player_id.clone()) {
+                        Ok(game) => self.start_game(Box::new(game)),
+                        Err(msg) => println!("{}", msg),
+                    }
+                }
+                None => {
+                    let core = Core::new(&self.fs, &self.scenario_path, self.seed);
+                    println!("Seed: {}", core.seed());
+                    self.start_game(Box::new(core));
+                }
+            },
+            Some(StateChangeCommand::LoadGame) => {
+                if self.save_path.exists() {
+                    let core = Core::load(&self.fs, &self.save_path);
+                    self.start_game(Box::new(core));
+                } else {
+                    println!("No saved game: {}", self.save_path.display());
+                }
+            }
+            Some(StateChangeCommand::StartReplay) => {
+                if self.replay_path.exists() {
+                    let visualizer = Box::new(ReplayStateVisualizer::new(
+                        &self.fs,
+                        &self.context,
+                        &self.replay_path,
+                    ));
+                    self.visualizers
+                        .push(visualizer as Box<dyn StateVisualizer>);
+                } else {
+                    println!("No replay: {}", self.replay_path.display());
+                }
+            }
+            Some(StateChangeCommand::EndGame) => {
+                let _ = self.visualizers.pop();
+            }
+            Some(StateChangeCommand::ShowGameOver(players, result)) => {
+                let _ = self.visualizers.pop();
+                let visualizer = Box::new(GameOverStateVisualizer::new(
+                    &self.context,
+                    &players,
+                    &result,
+                ));
                 self.visualizers
                     .push(visualizer as Box<dyn StateVisualizer>);
             }
-            Some(StateChangeCommand::EndGame) => {
+            Some(StateChangeCommand::ShowHandover(player_name, summary)) => {
+                let visualizer = Box::new(HandoverStateVisualizer::new(
+                    &self.context,
+                    &player_name,
+                    &summary,
+                ));
+                self.visualizers
+                    .push(visualizer as Box<dyn StateVisualizer>);
+            }
+            Some(StateChangeCommand::EndHandover) => {
                 let _ = self.visualizers.pop();
             }
             Some(StateChangeCommand::QuitMenu) => {
@@ -0,0 +1,327 @@
+// See LICENSE file for copyright and license details.
+
+use crate::command::{parse_command, TextCommand, HELP};
+use crate::render::{
+    colorize, describe_event, describe_unit, draw_map, format_pos, get_player_name,
+};
+use marauder_core::ai::Ai;
+use marauder_core::core::Command::{
+    CommandAttackUnit, CommandCreateUnit, CommandEndTurn, CommandMove, CommandOverwatch,
+};
+use marauder_core::core::{Command, Core, Event, Player, Unit};
+use marauder_core::game_state::GameState;
+use marauder_core::map::TerrainMap;
+use marauder_core::pathfinder::Pathfinder;
+use marauder_core::types::{MInt, MapPos, PlayerId, Size2, UnitId};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+fn get_game_states(players: &[Player], map: &TerrainMap) -> HashMap<PlayerId, GameState> {
+    let mut m = HashMap::new();
+    for player in players.iter() {
+        let _ = m.insert(player.id.clone(), GameState::new(map.clone()));
+    }
+    m
+}
+
+fn get_ais(players: &[Player], map_size: Size2<MInt>) -> HashMap<PlayerId, Ai> {
+    let mut m = HashMap::new();
+    for player in players.iter() {
+        if player.is_ai {
+            let _ = m.insert(player.id.clone(), Ai::new(player.id.clone(), map_size));
+        }
+    }
+    m
+}
+
+// Plays the game in a terminal: every human player types commands
+// and sees the map through its own fog of war.
+pub struct TermClient {
+    core: Core,
+    game_states: HashMap<PlayerId, GameState>,
+    pathfinder: Pathfinder,
+    ais: HashMap<PlayerId, Ai>,
+    selected_unit_id: Option<UnitId>,
+    // player whose turn header was printed last
+    shown_player_id: Option<PlayerId>,
+    use_color: bool,
+    is_running: bool,
+}
+
+impl TermClient {
+    pub fn new(core: Core, use_color: bool) -> TermClient {
+        let map_size = core.map_size();
+        let game_states = get_game_states(core.players(), core.map());
+        let ais = get_ais(core.players(), map_size);
+        TermClient {
+            core,
+            game_states,
+            pathfinder: Pathfinder::new(map_size),
+            ais,
+            selected_unit_id: None,
+            shown_player_id: None,
+            use_color,
+            is_running: true,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    fn is_ai_turn(&self) -> bool {
+        self.ais.contains_key(&self.core.player_id())
+    }
+
+    fn state(&self) -> &GameState {
+        &self.game_states[&self.core.player_id()]
+    }
+
+    // Events of AI players are not printed: they are not seen by humans
+    fn process_events(&mut self) {
+        let player_id = self.core.player_id();
+        let is_ai_turn = self.is_ai_turn();
+        while let Some(event) = self.core.get_event() {
+            let state = self.game_states.get_mut(&player_id).unwrap();
+            let is_game_over = match event {
+                Event::EventGameOver(_) => true,
+                Event::EventEndTurn(_, _) => {
+                    // show the map again when the turn comes back
+                    self.shown_player_id = None;
+                    false
+                }
+                _ => false,
+            };
+            if !is_ai_turn || is_game_over {
+                let lines =
+                    describe_event(self.core.object_types(), self.core.players(), state, &event);
+                for line in lines.iter() {
+                    println!("{}", line);
+                }
+            }
+            state.apply_event(self.core.object_types(), &event);
+            if is_game_over {
+                self.is_running = false;
+            }
+        }
+        // selected unit could be killed or moved
+        if let Some(unit_id) = self.selected_unit_id {
+            if self.state().units.contains_key(&unit_id) {
+                self.fill_pathfinder_map(unit_id);
+            } else {
+                self.selected_unit_id = None;
+            }
+        }
+    }
+
+    fn fill_pathfinder_map(&mut self, unit_id: UnitId) {
+        let state = &self.game_states[&self.core.player_id()];
+        self.pathfinder
+            .fill_map(self.core.object_types(), state, &state.units[&unit_id]);
+    }
+
+    fn do_command(&mut self, command: Command) -> bool {
+        match self.core.do_command(command) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("Command rejected: {}", err);
+                false
+            }
+        }
+    }
+
+    // Shows results of human player's command right away
+    fn do_player_command(&mut self, command: Command) {
+        if self.do_command(command) {
+            self.process_events();
+            if self.is_running {
+                self.print_map();
+            }
+        }
+    }
+
+    fn do_ai_command(&mut self) {
+        let player_id = self.core.player_id();
+        let command = match self.ais.get_mut(&player_id) {
+            Some(ai) => ai.get_command(self.core.object_types(), &self.game_states[&player_id]),
+            None => return,
+        };
+        match command {
+            CommandEndTurn => self.end_turn(),
+            command => {
+                // AI sees less than core, so give up the turn instead of looping
+                if !self.do_command(command) {
+                    self.end_turn();
+                }
+            }
+        }
+    }
+
+    fn end_turn(&mut self) {
+        let _ = self.do_command(CommandEndTurn);
+        self.selected_unit_id = None;
+    }
+
+    fn unit_at(&self, pos: MapPos) -> Option<&Unit> {
+        self.state().units_at(pos).into_iter().next()
+    }
+
+    fn select_unit(&mut self, pos: MapPos) {
+        let player_id = self.core.player_id();
+        let unit_id = match self.unit_at(pos) {
+            Some(unit) if unit.player_id == player_id => unit.id,
+            Some(_) => {
+                println!("Unit at {} belongs to another player", format_pos(pos));
+                return;
+            }
+            None => {
+                println!("No unit at {}", format_pos(pos));
+                return;
+            }
+        };
+        self.selected_unit_id = Some(unit_id);
+        self.fill_pathfinder_map(unit_id);
+        self.print_map();
+    }
+
+    fn move_unit(&mut self, pos: MapPos) {
+        let unit_id = match self.selected_unit_id {
+            Some(unit_id) => unit_id,
+            None => {
+                println!("No unit selected");
+                return;
+            }
+        };
+        // unreachable tiles have no path
+        let path = match self.pathfinder.get_path(pos) {
+            Some(ref path) if path.len() > 1 => path.clone(),
+            _ => {
+                println!("Can not reach {}", format_pos(pos));
+                return;
+            }
+        };
+        self.do_player_command(CommandMove(unit_id, path));
+    }
+
+    fn attack_unit(&mut self, pos: MapPos, weapon_number: Option<MInt>) {
+        let attacker_id = match self.selected_unit_id {
+            Some(unit_id) => unit_id,
+            None => {
+                println!("No unit selected");
+                return;
+            }
+        };
+        let defender_id = match self.unit_at(pos) {
+            Some(unit) => unit.id,
+            None => {
+                println!("No unit at {}", format_pos(pos));
+                return;
+            }
+        };
+        // core counts weapons from zero
+        let weapon_index = weapon_number.map(|n| n - 1);
+        self.do_player_command(CommandAttackUnit(attacker_id, defender_id, weapon_index));
+    }
+
+    fn overwatch(&mut self) {
+        match self.selected_unit_id {
+            Some(unit_id) => self.do_player_command(CommandOverwatch(unit_id)),
+            None => println!("No unit selected"),
+        }
+    }
+
+    fn create_unit(&mut self, pos: MapPos) {
+        self.do_player_command(CommandCreateUnit(pos));
+    }
+
+    fn print_map(&self) {
+        println!(
+            "{}",
+            draw_map(
+                self.core.object_types(),
+                self.state(),
+                &self.core.player_id(),
+                self.selected_unit_id,
+                &self.pathfinder,
+                self.use_color,
+            )
+        );
+    }
+
+    fn print_units(&self) {
+        let mut units: Vec<&Unit> = self.state().units.values().collect();
+        units.sort_by_key(|unit| unit.id);
+        for unit in units.into_iter() {
+            let text = describe_unit(self.core.object_types(), self.core.players(), unit);
+            println!("{}", colorize(&text, &unit.player_id, self.use_color));
+        }
+    }
+
+    fn print_turn_header(&mut self) {
+        let player_id = self.core.player_id();
+        if self.shown_player_id == Some(player_id.clone()) {
+            return;
+        }
+        let name = get_player_name(self.core.players(), &player_id);
+        println!();
+        println!("== {} ==", colorize(&name, &player_id, self.use_color));
+        self.print_map();
+        self.shown_player_id = Some(player_id);
+    }
+
+    fn print_prompt(&self) {
+        let player_id = self.core.player_id();
+        let name = get_player_name(self.core.players(), &player_id);
+        print!("{}> ", colorize(&name, &player_id, self.use_color));
+        std::io::stdout().flush().unwrap();
+    }
+
+    fn handle_command(&mut self, command: TextCommand) {
+        match command {
+            TextCommand::Select(pos) => self.select_unit(pos),
+            TextCommand::Move(pos) => self.move_unit(pos),
+            TextCommand::Attack(pos, weapon_number) => self.attack_unit(pos, weapon_number),
+            TextCommand::CreateUnit(pos) => self.create_unit(pos),
+            TextCommand::Overwatch => self.overwatch(),
+            TextCommand::EndTurn => self.end_turn(),
+            TextCommand::Units => self.print_units(),
+            TextCommand::Map => self.print_map(),
+            TextCommand::Help => println!("{}", HELP),
+            TextCommand::Quit => self.is_running = false,
+        }
+    }
+
+    // One AI command or one line of input
+    pub fn tick(&mut self, input: &mut dyn BufRead) {
+        self.process_events();
+        if !self.is_running {
+            return;
+        }
+        if self.is_ai_turn() {
+            self.do_ai_command();
+            return;
+        }
+        self.print_turn_header();
+        self.print_prompt();
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) => {
+                // end of input
+                println!();
+                self.is_running = false;
+            }
+            Ok(_) => {
+                if line.trim().is_empty() {
+                    return;
+                }
+                match parse_command(&line) {
+                    Ok(command) => self.handle_command(command),
+                    Err(msg) => println!("{}", msg),
+                }
+            }
+            Err(msg) => panic!("Can not read input: {}", msg),
+        }
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,100 @@
+// See LICENSE file for copyright and license details.
+
+use cgmath::Vector2;
+use marauder_core::types::{MInt, MapPos};
+
+pub enum TextCommand {
+    Select(MapPos),
+    Move(MapPos),
+    // target and 1-based weapon number, None picks the best weapon
+    Attack(MapPos, Option<MInt>),
+    CreateUnit(MapPos),
+    Overwatch,
+    EndTurn,
+    Units,
+    Map,
+    Help,
+    Quit,
+}
+
+pub const HELP: &str = "\
+commands:
+  select x,y  (s)  select own unit in tile
+  move x,y    (m)  move selected unit to tile
+  attack x,y [n]
+              (a)  attack unit in tile with selected unit,
+                   optionally with its n-th weapon
+  create x,y  (c)  create new unit in tile
+  overwatch   (o)  keep attack of selected unit for enemy's turn
+  end         (e)  end turn
+  units       (u)  list visible units
+  map         (p)  print map
+  help        (h)  show this help
+  quit        (q)  quit game";
+
+fn parse_coord(s: &str) -> Result<MInt, String> {
+    match s.trim().parse() {
+        Ok(n) => Ok(n),
+        Err(_) => Err(format!("bad coordinate '{}'", s)),
+    }
+}
+
+// Accepts "x,y", "x, y" and "x y"
+fn parse_pos(args: &str) -> Result<MapPos, String> {
+    let parts: Vec<&str> = args
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if parts.len() != 2 {
+        return Err("expected position as x,y".to_string());
+    }
+    Ok(MapPos {
+        v: Vector2 {
+            x: parse_coord(parts[0])?,
+            y: parse_coord(parts[1])?,
+        },
+    })
+}
+
+// Position followed by an optional weapon number
+fn parse_attack(args: &str) -> Result<TextCommand, String> {
+    let parts: Vec<&str> = args
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .collect();
+    match parts.len() {
+        2 => Ok(TextCommand::Attack(parse_pos(args)?, None)),
+        3 => {
+            let pos = parse_pos(&parts[..2].join(","))?;
+            let number = match parts[2].parse() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(format!("bad weapon number '{}'", parts[2])),
+            };
+            Ok(TextCommand::Attack(pos, Some(number)))
+        }
+        _ => Err("expected position as x,y and optional weapon number".to_string()),
+    }
+}
+
+pub fn parse_command(line: &str) -> Result<TextCommand, String> {
+    let line = line.trim();
+    let (name, args) = match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], &line[i..]),
+        None => (line, ""),
+    };
+    match name {
+        "select" | "s" => Ok(TextCommand::Select(parse_pos(args)?)),
+        "move" | "m" => Ok(TextCommand::Move(parse_pos(args)?)),
+        "attack" | "a" => parse_attack(args),
+        "create" | "c" => Ok(TextCommand::CreateUnit(parse_pos(args)?)),
+        "overwatch" | "o" => Ok(TextCommand::Overwatch),
+        "end" | "e" => Ok(TextCommand::EndTurn),
+        "units" | "u" => Ok(TextCommand::Units),
+        "map" | "p" => Ok(TextCommand::Map),
+        "help" | "h" => Ok(TextCommand::Help),
+        "quit" | "q" => Ok(TextCommand::Quit),
+        _ => Err(format!("unknown command '{}', try 'help'", name)),
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,62 @@
+// See LICENSE file for copyright and license details.
+
+#![warn(non_upper_case_globals)]
+#![warn(unused_results)]
+
+extern crate cgmath;
+extern crate marauder_core;
+
+use client::TermClient;
+use marauder_core::core::Core;
+use marauder_core::fs::FileSystem;
+use std::path::PathBuf;
+
+mod client;
+mod command;
+mod render;
+
+struct Args {
+    scenario_path: PathBuf,
+    seed: Option<u64>,
+    use_color: bool,
+}
+
+// Usage: marauder_term [--seed <n>] [--no-color] [scenario_path]
+fn parse_args() -> Args {
+    let mut args = Args {
+        scenario_path: PathBuf::from("data/scenarios/default.json"),
+        seed: None,
+        use_color: true,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--seed" {
+            let seed = match iter.next() {
+                Some(seed) => seed,
+                None => panic!("--seed: missing value"),
+            };
+            args.seed = match seed.parse() {
+                Ok(seed) => Some(seed),
+                Err(msg) => panic!("--seed: bad value '{}': {}", seed, msg),
+            };
+        } else if arg == "--no-color" {
+            args.use_color = false;
+        } else {
+            args.scenario_path = PathBuf::from(arg);
+        }
+    }
+    args
+}
+
+fn main() {
+    let args = parse_args();
+    let core = Core::new(&FileSystem::new(), &args.scenario_path, args.seed);
+    let mut client = TermClient::new(core, args.use_color);
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    while client.is_running() {
+        client.tick(&mut input);
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab:
@@ -0,0 +1,294 @@
+// See LICENSE file for copyright and license details.
+
+use marauder_core::combat::{AttackResult, ShotResult};
+use marauder_core::core::{Event, ObjectTypes, Player, Unit};
+use marauder_core::game_state::GameState;
+use marauder_core::map::MapPosIter;
+use marauder_core::pathfinder::{Pathfinder, Tile};
+use marauder_core::types::{MInt, MapPos, PlayerId, UnitId};
+use marauder_core::victory::{GameResult, VictoryReason};
+
+// Every tile takes this many columns, even rows are shifted by half of it
+const TILE_WIDTH: usize = 4;
+
+// ANSI foreground colors, same order as marker colors of the visualizer
+const PLAYER_COLORS: [MInt; 6] = [34, 31, 32, 33, 35, 36];
+
+pub fn colorize(text: &str, player_id: &PlayerId, use_color: bool) -> String {
+    if use_color {
+        let color = PLAYER_COLORS[player_id.id as usize % PLAYER_COLORS.len()];
+        format!("\x1b[1;{}m{}\x1b[0m", color, text)
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn format_pos(pos: MapPos) -> String {
+    format!("{},{}", pos.v.x, pos.v.y)
+}
+
+pub fn get_player_name(players: &[Player], player_id: &PlayerId) -> String {
+    players[player_id.id as usize].name.clone()
+}
+
+fn get_unit_type_name(object_types: &ObjectTypes, unit: &Unit) -> String {
+    object_types.get_unit_type(unit.type_id).name.clone()
+}
+
+// Own units are uppercase, enemies are lowercase
+fn get_unit_symbol(object_types: &ObjectTypes, unit: &Unit, player_id: &PlayerId) -> char {
+    let symbol = get_unit_type_name(object_types, unit)
+        .chars()
+        .next()
+        .unwrap();
+    if unit.player_id == *player_id {
+        symbol.to_ascii_uppercase()
+    } else {
+        symbol.to_ascii_lowercase()
+    }
+}
+
+fn draw_tile(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    player_id: &PlayerId,
+    selected_unit_id: Option<UnitId>,
+    // None if selected unit can not reach the tile
+    reachable_tile: Option<&Tile>,
+    pos: MapPos,
+    use_color: bool,
+) -> String {
+    match state.units_at(pos).first() {
+        Some(unit) => {
+            let symbol = get_unit_symbol(object_types, unit, player_id).to_string();
+            let (left, right) = if Some(unit.id) == selected_unit_id {
+                ("[", "]")
+            } else {
+                (" ", " ")
+            };
+            format!(
+                " {}{}{}",
+                left,
+                colorize(&symbol, &unit.player_id, use_color),
+                right
+            )
+        }
+        None => {
+            let terrain_type_id = state.map.terrain_type_id(pos);
+            let symbol = object_types.get_terrain_type(terrain_type_id).symbol;
+            match reachable_tile {
+                Some(tile) if tile.is_stop => format!(" <{}>", symbol),
+                Some(_) => format!(" ({})", symbol),
+                None => format!("  {} ", symbol),
+            }
+        }
+    }
+}
+
+// Tiles that selected unit can reach are drawn in parentheses,
+// ones in enemies' zones of control where it has to stop in angle brackets
+pub fn draw_map(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    player_id: &PlayerId,
+    selected_unit_id: Option<UnitId>,
+    pathfinder: &Pathfinder,
+    use_color: bool,
+) -> String {
+    let map_size = state.map.get_size();
+    let mut text = String::new();
+    text.push_str("   ");
+    for x in 0..map_size.w {
+        text.push_str(&format!("{:>3} ", x));
+    }
+    for pos in MapPosIter::new(map_size) {
+        if pos.v.x == 0 {
+            text.push_str(&format!("\n{:>3}", pos.v.y));
+            if pos.v.y % 2 == 0 {
+                text.push_str(&" ".repeat(TILE_WIDTH / 2));
+            }
+        }
+        let tile = pathfinder.get_map().tile(pos);
+        let is_reachable = selected_unit_id.is_some() && tile.parent.is_some();
+        text.push_str(&draw_tile(
+            object_types,
+            state,
+            player_id,
+            selected_unit_id,
+            if is_reachable { Some(tile) } else { None },
+            pos,
+            use_color,
+        ));
+    }
+    text
+}
+
+pub fn describe_unit(object_types: &ObjectTypes, players: &[Player], unit: &Unit) -> String {
+    let unit_type = object_types.get_unit_type(unit.type_id);
+    let mut text = format!(
+        "{} {} {} at {}",
+        get_player_name(players, &unit.player_id),
+        unit_type.name,
+        unit.id.id,
+        format_pos(unit.pos),
+    );
+    if unit_type.count > 1 {
+        text.push_str(&format!(", models {}/{}", unit.count, unit_type.count));
+    }
+    let weapon_names: Vec<&str> = unit_type
+        .weapon_type_ids
+        .iter()
+        .map(|&id| object_types.get_weapon_type(id).name.as_str())
+        .collect();
+    text.push_str(&format!(", weapons: {}", weapon_names.join(", ")));
+    text.push_str(&format!(
+        ", move points {}/{}",
+        unit.move_points, unit_type.move_points
+    ));
+    if unit.attacked {
+        text.push_str(", attacked");
+    }
+    if unit.overwatch {
+        text.push_str(", on overwatch");
+    }
+    text
+}
+
+pub fn describe_game_result(players: &[Player], result: &GameResult) -> Vec<String> {
+    let mut lines = Vec::new();
+    let reason = match result.reason {
+        VictoryReason::EnemiesDestroyed => "all enemy units destroyed",
+        VictoryReason::ObjectivesHeld => "objectives held",
+        VictoryReason::TurnLimit => "turn limit reached",
+    };
+    lines.push(match result.winner {
+        Some(ref winner) => format!("{} wins: {}", get_player_name(players, winner), reason),
+        None => format!("Draw: {}", reason),
+    });
+    lines.push(format!("turns: {}", result.turns));
+    for stats in result.stats.iter() {
+        lines.push(format!(
+            "{}: points {}, killed {}, lost {}",
+            get_player_name(players, &stats.player_id),
+            stats.victory_points,
+            stats.units_killed,
+            stats.units_lost,
+        ));
+    }
+    lines
+}
+
+fn get_unit_name(object_types: &ObjectTypes, state: &GameState, unit_id: UnitId) -> String {
+    match state.units.get(&unit_id) {
+        Some(unit) => format!("{} {}", get_unit_type_name(object_types, unit), unit_id.id),
+        None => format!("unit {}", unit_id.id),
+    }
+}
+
+// Outcome and every roll of one shot,
+// e.g. "armor deflected (hit 2 < 3, pierce 4 >= 2)"
+fn describe_shot_result(result: &ShotResult) -> String {
+    let test_names = ["hit", "pierce", "wound"];
+    let rolls: Vec<String> = result
+        .rolls
+        .iter()
+        .zip(test_names.iter())
+        .map(|(test_roll, name)| {
+            let sign = if test_roll.is_passed() { "<" } else { ">=" };
+            format!("{} {} {} {}", name, test_roll.roll, sign, test_roll.needed)
+        })
+        .collect();
+    format!("{} ({})", result.outcome, rolls.join(", "))
+}
+
+// Every shot on its own line
+fn describe_attack(
+    object_types: &ObjectTypes,
+    state: &GameState,
+    attacker_id: UnitId,
+    defender_id: UnitId,
+    result: &AttackResult,
+) -> Vec<String> {
+    let defender_name = get_unit_name(object_types, state, defender_id);
+    let attacker_name = get_unit_name(object_types, state, attacker_id);
+    let mut lines = vec![match state.units.get(&attacker_id) {
+        Some(attacker) => format!(
+            "{} {} {} with {}",
+            attacker_name,
+            if attacker.overwatch {
+                "fires from overwatch at"
+            } else {
+                "attacks"
+            },
+            defender_name,
+            object_types
+                .get_unit_weapon_type(attacker.type_id, result.weapon_index)
+                .name,
+        ),
+        None => format!("{} attacks {}", attacker_name, defender_name),
+    }];
+    for shot in result.shots.iter() {
+        lines.push(format!("  {}", describe_shot_result(shot)));
+    }
+    match state.units.get(&defender_id) {
+        Some(defender) if result.is_destroyed(defender) => {
+            lines.push(format!("{} is destroyed", defender_name));
+        }
+        Some(defender) if result.killed_count() > 0 => {
+            lines.push(format!(
+                "{} has {} of {} models left",
+                defender_name,
+                defender.count - result.killed_count(),
+                object_types.get_unit_type(defender.type_id).count,
+            ));
+        }
+        _ => {}
+    }
+    lines
+}
+
+// Must be called before the event is applied to the state
+pub fn describe_event(
+    object_types: &ObjectTypes,
+    players: &[Player],
+    state: &GameState,
+    event: &Event,
+) -> Vec<String> {
+    match event {
+        Event::EventMove(unit_id, ref path) => vec![format!(
+            "{} moves to {}",
+            get_unit_name(object_types, state, *unit_id),
+            format_pos(*path.last().unwrap()),
+        )],
+        Event::EventEndTurn(old_player_id, new_player_id) => vec![format!(
+            "{} ends turn, {} plays",
+            get_player_name(players, old_player_id),
+            get_player_name(players, new_player_id),
+        )],
+        Event::EventCreateUnit(unit_id, pos, type_id, player_id) => vec![format!(
+            "{} creates {} {} at {}",
+            get_player_name(players, player_id),
+            object_types.get_unit_type(*type_id).name,
+            unit_id.id,
+            format_pos(*pos),
+        )],
+        Event::EventAttackUnit(attacker_id, defender_id, ref result) => {
+            describe_attack(object_types, state, *attacker_id, *defender_id, result)
+        }
+        Event::EventOverwatch(unit_id) => vec![format!(
+            "{} is on overwatch",
+            get_unit_name(object_types, state, *unit_id)
+        )],
+        Event::EventShowUnit(ref unit) => vec![format!(
+            "spotted {}",
+            describe_unit(object_types, players, unit)
+        )],
+        Event::EventHideUnit(unit_id) => vec![format!(
+            "lost sight of {}",
+            get_unit_name(object_types, state, *unit_id)
+        )],
+        Event::EventGameOver(ref result) => describe_game_result(players, result),
+    }
+}
+
+// vim: set tabstop=4 shiftwidth=4 softtabstop=4 expandtab: